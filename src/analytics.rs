@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+
+use super::{
+    error::Error,
+    media, parse,
+    types::{Comment, Post},
+    Client, RequestOptions,
+};
+
+/// Whether two URLs point to the same article.
+///
+/// Ignores the scheme, a `www.` prefix, the query, the fragment and a
+/// trailing slash.
+pub fn same_article(a: &str, b: &str) -> bool {
+    let (host_a, path_a) = media::host_and_path(a);
+    let (host_b, path_b) = media::host_and_path(b);
+    host_a == host_b
+        && path_a.trim_end_matches('/') == path_b.trim_end_matches('/')
+}
+
+/// Submissions found by [submission_history].
+#[derive(Clone, Debug)]
+pub struct SubmissionHistory {
+    /// Newest first.
+    pub posts: Vec<Post>,
+    /// The page limit was reached before the end of the listing, so older
+    /// submissions may be missing.
+    pub truncated: bool,
+}
+
+/// A place to look for submissions of an article, for
+/// [submission_history_from].
+///
+/// [SiteListing] scans HN itself. Implement this for other sources, eg.
+/// Algolia's search API or a local store of crawled posts.
+pub trait HistorySource {
+    /// Submissions of `url` known to this source, newest first.
+    fn submissions(&self, url: &str) -> Result<SubmissionHistory, Error>;
+}
+
+/// Scans HN's per-site listing (`from?site=`) for posts linking to the
+/// same article.
+///
+/// Submissions HN files under another site, eg. through a URL shortener,
+/// are not found.
+#[derive(Clone)]
+pub struct SiteListing<'a> {
+    client: &'a Client,
+    max_pages: usize,
+}
+
+impl<'a> SiteListing<'a> {
+    /// Follow at most `max_pages` pages of the listing.
+    pub fn new(client: &'a Client, max_pages: usize) -> Self {
+        Self { client, max_pages }
+    }
+}
+
+impl<'a> HistorySource for SiteListing<'a> {
+    fn submissions(&self, url: &str) -> Result<SubmissionHistory, Error> {
+        let (site, _) = media::host_and_path(url);
+        if site.is_empty() {
+            return Err(Error::Config(format!("Invalid URL: {}", url)));
+        }
+
+        let opts = RequestOptions::default();
+        let mut path = format!("from?site={}", site);
+        let mut posts = Vec::new();
+        for _ in 0..self.max_pages {
+            let page = self
+                .client
+                .get_dom(&path, &opts)?
+                .try_map(parse::parse_list)?
+                .into_inner();
+            posts.extend(page.items.into_iter().filter(|post| {
+                post.url.as_deref().is_some_and(|u| same_article(u, url))
+            }));
+            match page.next {
+                Some(cursor) => path = cursor.href().to_string(),
+                None => {
+                    return Ok(SubmissionHistory {
+                        posts,
+                        truncated: false,
+                    })
+                }
+            }
+        }
+        Ok(SubmissionHistory {
+            posts,
+            truncated: true,
+        })
+    }
+}
+
+/// Every submission of `url` HN lists under its site, newest first.
+///
+/// Only searches HN's per-site listing, following at most `max_pages`
+/// pages, see [SiteListing]. Use [submission_history_from] to combine it
+/// with other sources.
+pub fn submission_history(
+    client: &Client,
+    url: &str,
+    max_pages: usize,
+) -> Result<SubmissionHistory, Error> {
+    SiteListing::new(client, max_pages).submissions(url)
+}
+
+/// Every submission of `url` found in any of `sources`, newest first.
+///
+/// Posts found by several sources are reported once, as found by the
+/// first of them. The history is truncated if any source's is.
+pub fn submission_history_from(
+    sources: &[&dyn HistorySource],
+    url: &str,
+) -> Result<SubmissionHistory, Error> {
+    let mut posts = Vec::<Post>::new();
+    let mut truncated = false;
+    for source in sources {
+        let history = source.submissions(url)?;
+        truncated |= history.truncated;
+        for post in history.posts {
+            if !posts.iter().any(|known| known.id == post.id) {
+                posts.push(post);
+            }
+        }
+    }
+    // HN ids grow over time.
+    posts.sort_by_key(|post| {
+        std::cmp::Reverse(post.id.parse::<u64>().unwrap_or(0))
+    });
+    Ok(SubmissionHistory { posts, truncated })
+}
+
+/// Something with a creation time, for [bucket_by_hour] and
+/// [bucket_by_day].
+pub trait Timestamped {
+    /// Unix timestamp in seconds, if known.
+    fn timestamp(&self) -> Option<u64>;
+
+    /// Score, if the item has one.
+    fn score(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl Timestamped for Post {
+    fn timestamp(&self) -> Option<u64> {
+        self.meta.timestamp
+    }
+
+    fn score(&self) -> Option<u64> {
+        self.score
+    }
+}
+
+impl Timestamped for Comment {
+    fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+}
+
+/// Items created within one time bucket.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bucket {
+    /// Start of the bucket as a Unix timestamp, in seconds.
+    pub start: u64,
+    pub count: usize,
+    /// Scores of the items that have one, in ascending order.
+    pub scores: Vec<u64>,
+}
+
+impl Bucket {
+    pub fn mean_score(&self) -> Option<f64> {
+        if self.scores.is_empty() {
+            return None;
+        }
+        let total = self.scores.iter().sum::<u64>();
+        Some(total as f64 / self.scores.len() as f64)
+    }
+
+    /// The median score, rounded down for an even number of scores.
+    pub fn median_score(&self) -> Option<u64> {
+        let len = self.scores.len();
+        match len {
+            0 => None,
+            _ if len % 2 == 1 => Some(self.scores[len / 2]),
+            _ => Some((self.scores[len / 2 - 1] + self.scores[len / 2]) / 2),
+        }
+    }
+}
+
+/// Group items by the UTC hour they were created in.
+///
+/// Buckets are returned in chronological order; hours without items and
+/// items without a timestamp are left out.
+pub fn bucket_by_hour<'a, T: Timestamped + 'a>(
+    items: impl IntoIterator<Item = &'a T>,
+) -> Vec<Bucket> {
+    bucket(items, 60 * 60)
+}
+
+/// Group items by the UTC day they were created on, like [bucket_by_hour].
+pub fn bucket_by_day<'a, T: Timestamped + 'a>(
+    items: impl IntoIterator<Item = &'a T>,
+) -> Vec<Bucket> {
+    bucket(items, 24 * 60 * 60)
+}
+
+fn bucket<'a, T: Timestamped + 'a>(
+    items: impl IntoIterator<Item = &'a T>,
+    width: u64,
+) -> Vec<Bucket> {
+    let mut buckets = BTreeMap::new();
+    for item in items {
+        let timestamp = match item.timestamp() {
+            Some(timestamp) => timestamp,
+            None => continue,
+        };
+        let start = timestamp - timestamp % width;
+        let bucket = buckets.entry(start).or_insert_with(|| Bucket {
+            start,
+            count: 0,
+            scores: Vec::new(),
+        });
+        bucket.count += 1;
+        bucket.scores.extend(item.score());
+    }
+    buckets
+        .into_values()
+        .map(|mut bucket| {
+            bucket.scores.sort_unstable();
+            bucket
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_support::{list_fixture, mock_client},
+        transport::MockTransport,
+    };
+
+    #[test]
+    fn test_bucket_by_hour() {
+        let html = list_fixture(&[1, 2, 3])
+            .replace(
+                "<span class='score'>1 points",
+                "<span class='age' title='2023-10-05T14:03:11'>\
+                 <a>1 hour ago</a></span><span class='score'>1 points",
+            )
+            .replace(
+                "<span class='score'>2 points",
+                "<span class='age' title='2023-10-05T14:59:59 1696517999'>\
+                 <a>1 hour ago</a></span><span class='score'>2 points",
+            );
+        let page =
+            parse::parse_list_html(&html, parse::Layout::Current).unwrap();
+        assert_eq!(page.items[0].meta.timestamp, Some(1_696_514_591));
+
+        let hours = bucket_by_hour(page.items.iter());
+        assert_eq!(hours.len(), 1);
+        assert_eq!(hours[0].start, 1_696_514_400);
+        assert_eq!(hours[0].count, 2);
+        assert_eq!(hours[0].scores, vec![1, 2]);
+        assert_eq!(hours[0].median_score(), Some(1));
+        assert_eq!(hours[0].mean_score(), Some(1.5));
+        let days = bucket_by_day(page.items.iter());
+        assert_eq!(days[0].start, 1_696_464_000);
+    }
+
+    #[test]
+    fn test_same_article() {
+        assert!(same_article(
+            "https://www.example.com/post/",
+            "http://example.com/post?utm_source=hn"
+        ));
+        assert!(!same_article(
+            "https://example.com/post",
+            "https://example.com/other"
+        ));
+    }
+
+    #[test]
+    fn test_submission_history() {
+        let first = list_fixture(&[1, 2]).replace(
+            "</body>",
+            "<a class='morelink' href='from?site=example.com&p=2'>More</a>\
+             </body>",
+        );
+        let second = list_fixture(&[3])
+            .replace("https://example.com/3", "https://www.example.com/1/");
+        let mock = MockTransport::new()
+            .page("from?site=example.com", &first)
+            .page("from?site=example.com&p=2", &second);
+        let client = mock_client(mock);
+        let url = "https://example.com/1";
+
+        let history = submission_history(&client, url, 1).unwrap();
+        let ids = history.posts.iter().map(|p| &p.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["1"]);
+        assert!(history.truncated);
+
+        let history = submission_history(&client, url, 10).unwrap();
+        let ids = history.posts.iter().map(|p| &p.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["1", "3"]);
+        assert!(!history.truncated);
+    }
+
+    #[test]
+    fn test_submission_history_from() {
+        struct Archive;
+
+        impl HistorySource for Archive {
+            fn submissions(
+                &self,
+                url: &str,
+            ) -> Result<SubmissionHistory, Error> {
+                let mut posts = vec![Post::new("2", "Old"), Post::new("1", "")];
+                for post in &mut posts {
+                    post.url = Some(url.to_string());
+                }
+                Ok(SubmissionHistory {
+                    posts,
+                    truncated: false,
+                })
+            }
+        }
+
+        let mock = MockTransport::new()
+            .page("from?site=example.com", &list_fixture(&[1, 3]));
+        let client = mock_client(mock);
+        let listing = SiteListing::new(&client, 1);
+        let url = "https://example.com/1";
+
+        let history =
+            submission_history_from(&[&listing, &Archive], url).unwrap();
+        let ids = history.posts.iter().map(|p| &p.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["2", "1"]);
+        assert_eq!(history.posts[1].title, "Story 1");
+        assert!(!history.truncated);
+    }
+}
@@ -0,0 +1,44 @@
+use super::error::Error;
+
+/// Outcome of a batch operation.
+///
+/// Batch operations do not stop at the first failure; every input ends up
+/// in either `succeeded` or `failed`.
+#[derive(Debug)]
+pub struct BatchReport<I, T> {
+    pub succeeded: Vec<(I, T)>,
+    pub failed: Vec<(I, Error)>,
+    /// Number of inputs that needed more than one attempt, see
+    /// [crate::RetryPolicy].
+    pub retried: usize,
+}
+
+impl<I, T> BatchReport<I, T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+            retried: 0,
+        }
+    }
+
+    /// Record the result for `input`, fetched with `retries` retries.
+    pub(crate) fn push(
+        &mut self,
+        input: I,
+        result: Result<T, Error>,
+        retries: usize,
+    ) {
+        if retries > 0 {
+            self.retried += 1;
+        }
+        match result {
+            Ok(value) => self.succeeded.push((input, value)),
+            Err(err) => self.failed.push((input, err)),
+        }
+    }
+
+    pub fn is_complete_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
@@ -0,0 +1,74 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// In-memory cache whose entries expire after a fixed time.
+///
+/// Expired entries are dropped when read, and at most once per `ttl` when
+/// inserting, so entries that are never read again don't pile up.
+#[derive(Debug)]
+pub(crate) struct TtlCache<V> {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, V)>>,
+    last_purge: Mutex<Instant>,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            last_purge: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match entries.get(key) {
+            Some((stored, value)) if stored.elapsed() < self.ttl => {
+                Some(value.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: &str, value: V) {
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut last_purge = self
+            .last_purge
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if last_purge.elapsed() >= self.ttl {
+            let ttl = self.ttl;
+            entries.retain(|_, (stored, _)| stored.elapsed() < ttl);
+            *last_purge = Instant::now();
+        }
+        entries.insert(key.to_string(), (Instant::now(), value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expired_entries_are_purged_on_insert() {
+        let cache = TtlCache::new(Duration::from_secs(0));
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        let entries = cache.entries.lock().unwrap();
+        assert_eq!(entries.keys().collect::<Vec<_>>(), vec!["b"]);
+    }
+}
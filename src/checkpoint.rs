@@ -0,0 +1,280 @@
+use std::path::PathBuf;
+
+use super::error::Error;
+#[cfg(feature = "redis")]
+use super::transport::{Method, Request, Response};
+
+/// Persistent storage for cursors and other progress markers of
+/// long-running operations.
+///
+/// Values are opaque strings, usually serialized JSON. Implement this for
+/// other backends (Redis, Postgres, ...) to share state between processes.
+pub trait Checkpoint: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<String>, Error>;
+    fn set(&self, key: &str, value: &str) -> Result<(), Error>;
+}
+
+/// Stores each key in its own file inside a directory.
+#[derive(Clone, Debug)]
+pub struct FileCheckpoint {
+    dir: PathBuf,
+}
+
+impl FileCheckpoint {
+    /// Use `dir` for storage, creating it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, Error> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // Escape everything but a safe set of characters to get a valid,
+        // collision free file name.
+        let name = key
+            .bytes()
+            .map(|b| match b {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => {
+                    (b as char).to_string()
+                }
+                _ => format!("%{:02X}", b),
+            })
+            .collect::<String>();
+        self.dir.join(name)
+    }
+}
+
+impl Checkpoint for FileCheckpoint {
+    fn get(&self, key: &str) -> Result<Option<String>, Error> {
+        match std::fs::read_to_string(self.path(key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), Error> {
+        // Write to a temporary file first so a crash never leaves a
+        // truncated checkpoint behind.
+        let path = self.path(key);
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, value)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+/// Stores checkpoints in a sled database.
+#[cfg(feature = "sled")]
+#[derive(Clone, Debug)]
+pub struct SledCheckpoint {
+    db: sled::Db,
+}
+
+#[cfg(feature = "sled")]
+impl SledCheckpoint {
+    pub fn new(db: sled::Db) -> Self {
+        Self { db }
+    }
+
+    /// Open (or create) a database at `path`.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        let db = sled::open(path).map_err(|e| Error::Storage(e.to_string()))?;
+        Ok(Self { db })
+    }
+}
+
+#[cfg(feature = "sled")]
+impl Checkpoint for SledCheckpoint {
+    fn get(&self, key: &str) -> Result<Option<String>, Error> {
+        let value = self
+            .db
+            .get(key)
+            .map_err(|e| Error::Storage(e.to_string()))?;
+        value
+            .map(|bytes| {
+                String::from_utf8(bytes.to_vec())
+                    .map_err(|e| Error::Storage(e.to_string()))
+            })
+            .transpose()
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), Error> {
+        self.db
+            .insert(key, value.as_bytes())
+            .map_err(|e| Error::Storage(e.to_string()))?;
+        self.db.flush().map_err(|e| Error::Storage(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Stores checkpoints in Redis, so multiple processes can share them.
+#[cfg(feature = "redis")]
+#[derive(Clone, Debug)]
+pub struct RedisCheckpoint {
+    client: redis::Client,
+    prefix: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisCheckpoint {
+    /// Connect to `url`, eg. `redis://127.0.0.1/`.
+    ///
+    /// All keys are stored with `prefix` prepended.
+    pub fn open(url: &str, prefix: impl Into<String>) -> Result<Self, Error> {
+        let client = redis::Client::open(url)
+            .map_err(|e| Error::Storage(e.to_string()))?;
+        Ok(Self {
+            client,
+            prefix: prefix.into(),
+        })
+    }
+
+    fn connection(&self) -> Result<redis::Connection, Error> {
+        self.client
+            .get_connection()
+            .map_err(|e| Error::Storage(e.to_string()))
+    }
+}
+
+#[cfg(feature = "redis")]
+impl Checkpoint for RedisCheckpoint {
+    fn get(&self, key: &str) -> Result<Option<String>, Error> {
+        use redis::Commands;
+
+        self.connection()?
+            .get(format!("{}{}", self.prefix, key))
+            .map_err(|e| Error::Storage(e.to_string()))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), Error> {
+        use redis::Commands;
+
+        self.connection()?
+            .set(format!("{}{}", self.prefix, key), value)
+            .map_err(|e| Error::Storage(e.to_string()))
+    }
+}
+
+/// Shares fetched pages between processes through Redis.
+///
+/// [RedisResponseCache::install] answers GET requests from the cache and
+/// stores successful responses for `ttl`. Pages are cached per URL, not
+/// per account, so don't install it on clients that log in.
+#[cfg(feature = "redis")]
+#[derive(Clone, Debug)]
+pub struct RedisResponseCache {
+    client: redis::Client,
+    prefix: String,
+    ttl: std::time::Duration,
+}
+
+#[cfg(feature = "redis")]
+impl RedisResponseCache {
+    /// Header marking responses served from the cache.
+    const HIT_HEADER: &'static str = "x-cache";
+
+    /// Connect to `url`, eg. `redis://127.0.0.1/`.
+    ///
+    /// All keys are stored with `prefix` prepended.
+    pub fn open(
+        url: &str,
+        prefix: impl Into<String>,
+        ttl: std::time::Duration,
+    ) -> Result<Self, Error> {
+        let client = redis::Client::open(url)
+            .map_err(|e| Error::Storage(e.to_string()))?;
+        Ok(Self {
+            client,
+            prefix: prefix.into(),
+            ttl,
+        })
+    }
+
+    /// Add the cache to `builder`, as an intercept and a response hook.
+    ///
+    /// Redis errors are treated as cache misses.
+    pub fn install(
+        self,
+        builder: crate::ClientBuilder,
+    ) -> crate::ClientBuilder {
+        let cache = self.clone();
+        builder
+            .intercept(move |request| cache.get(request).ok().flatten())
+            .on_response(move |request, res| {
+                if let Ok(res) = res {
+                    let _ = self.put(request, res);
+                }
+            })
+    }
+
+    fn connection(&self) -> Result<redis::Connection, Error> {
+        self.client
+            .get_connection()
+            .map_err(|e| Error::Storage(e.to_string()))
+    }
+
+    fn get(&self, request: &Request) -> Result<Option<Response>, Error> {
+        use redis::Commands;
+
+        if request.method != Method::Get {
+            return Ok(None);
+        }
+        let value: Option<String> = self
+            .connection()?
+            .get(format!("{}{}", self.prefix, request.url))
+            .map_err(|e| Error::Storage(e.to_string()))?;
+        let value = match value {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let (url, redirects, body): (String, Vec<String>, String) =
+            serde_json::from_str(&value)
+                .map_err(|e| Error::Storage(e.to_string()))?;
+        Ok(Some(Response {
+            status: 200,
+            url,
+            redirects,
+            headers: vec![(Self::HIT_HEADER.to_string(), "hit".to_string())],
+            body,
+        }))
+    }
+
+    fn put(&self, request: &Request, res: &Response) -> Result<(), Error> {
+        use redis::Commands;
+
+        if request.method != Method::Get
+            || res.status != 200
+            || res.header(Self::HIT_HEADER).is_some()
+        {
+            return Ok(());
+        }
+        let value =
+            serde_json::to_string(&(&res.url, &res.redirects, &res.body))
+                .map_err(|e| Error::Storage(e.to_string()))?;
+        // Redis expiry has a resolution of seconds.
+        let seconds = self.ttl.as_secs().max(1) as usize;
+        self.connection()?
+            .set_ex(format!("{}{}", self.prefix, request.url), value, seconds)
+            .map_err(|e| Error::Storage(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_checkpoint() {
+        let dir = std::env::temp_dir()
+            .join(format!("hn-checkpoint-test-{}", std::process::id()));
+        let store = FileCheckpoint::new(&dir).unwrap();
+        assert_eq!(store.get("newest/cursor").unwrap(), None);
+        store.set("newest/cursor", "{\"next\":42}").unwrap();
+        assert_eq!(
+            store.get("newest/cursor").unwrap().as_deref(),
+            Some("{\"next\":42}")
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
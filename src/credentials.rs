@@ -0,0 +1,87 @@
+//! Account credentials kept in the operating system's keyring.
+//!
+//! The keyring (Keychain on macOS, Credential Manager on Windows, the
+//! Secret Service on Linux) encrypts passwords at rest, so bots don't need
+//! to keep them in config files or environment variables.
+
+use super::{error::Error, AuthenticatedClient, Client};
+
+/// A username and password.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl Credentials {
+    pub fn new(
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+
+    /// Log in with these credentials, see [AuthenticatedClient::login_with].
+    pub fn login_with(
+        &self,
+        client: Client,
+    ) -> Result<AuthenticatedClient, Error> {
+        AuthenticatedClient::login_with(client, &self.username, &self.password)
+    }
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Stores passwords in the keyring, under a service name of your choice.
+#[derive(Clone, Debug)]
+pub struct KeyringStore {
+    service: String,
+}
+
+impl KeyringStore {
+    /// Use `service` to tell the entries apart from other applications',
+    /// eg. `my-bot.news.ycombinator.com`.
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    fn entry<'a>(&'a self, username: &'a str) -> keyring::Keyring<'a> {
+        keyring::Keyring::new(&self.service, username)
+    }
+
+    /// Load the credentials of `username`, or `None` if none are stored.
+    pub fn load(&self, username: &str) -> Result<Option<Credentials>, Error> {
+        match self.entry(username).get_password() {
+            Ok(password) => Ok(Some(Credentials::new(username, password))),
+            Err(keyring::KeyringError::NoPasswordFound) => Ok(None),
+            Err(e) => Err(Error::Storage(e.to_string())),
+        }
+    }
+
+    /// Store credentials, replacing any previous password of the user.
+    pub fn save(&self, credentials: &Credentials) -> Result<(), Error> {
+        self.entry(&credentials.username)
+            .set_password(&credentials.password)
+            .map_err(|e| Error::Storage(e.to_string()))
+    }
+
+    /// Remove the stored password of `username`, if any.
+    pub fn delete(&self, username: &str) -> Result<(), Error> {
+        match self.entry(username).delete_password() {
+            Ok(()) | Err(keyring::KeyringError::NoPasswordFound) => Ok(()),
+            Err(e) => Err(Error::Storage(e.to_string())),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use super::types::Post;
+
+/// A comment whose content changed between two fetches of a thread.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommentEdit {
+    pub id: String,
+    pub old_html: String,
+    pub new_html: String,
+}
+
+/// Find comments present in both snapshots of a thread whose content
+/// changed, in the display order of the newer snapshot.
+///
+/// Comments are compared by [crate::Comment::content_hash], so whitespace
+/// differences are not reported.
+pub fn detect_edits(old: &Post, new: &Post) -> Vec<CommentEdit> {
+    let old_comments = old
+        .comments
+        .iter()
+        .map(|comment| (comment.id.as_str(), comment))
+        .collect::<HashMap<_, _>>();
+
+    new.comments
+        .iter()
+        .filter_map(|comment| {
+            let previous = old_comments.get(comment.id.as_str())?;
+            if previous.content_hash() == comment.content_hash() {
+                return None;
+            }
+            Some(CommentEdit {
+                id: comment.id.clone(),
+                old_html: previous.content_html.clone(),
+                new_html: comment.content_html.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, test_support::thread_fixture};
+
+    #[test]
+    fn test_detect_edits() {
+        let parse_thread = |comments: &[(u64, u32)]| {
+            let html = thread_fixture(comments);
+            parse::parse_submission(
+                "1".into(),
+                scraper::Html::parse_document(&html),
+            )
+            .unwrap()
+        };
+        let old = parse_thread(&[(2, 0), (3, 1)]);
+        let mut new = parse_thread(&[(2, 0), (3, 1), (4, 0)]);
+        assert!(detect_edits(&old, &new).is_empty());
+
+        let edited = new.comments.iter_mut().nth(1).unwrap();
+        edited.content_html = "<span>Edited</span>".into();
+        let edits = detect_edits(&old, &new);
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].id, "3");
+        assert_eq!(edits[0].new_html, "<span>Edited</span>");
+    }
+}
@@ -0,0 +1,85 @@
+use super::media::host_and_path;
+
+/// Structured metadata derived from a story URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Enrichment {
+    /// A GitHub repository, eg. `rust-lang/rust`.
+    GitHubRepo { owner: String, name: String },
+    /// An arXiv paper id, eg. `1706.03762`.
+    ArxivId(String),
+    /// Metadata produced by a user-provided [Enricher].
+    Custom { kind: String, value: String },
+}
+
+/// Derives an [Enrichment] from a story URL.
+///
+/// See [GitHubRepo] and [Arxiv] for the bundled implementations.
+pub trait Enricher {
+    fn enrich(&self, url: &str) -> Option<Enrichment>;
+}
+
+/// Extracts `owner/name` from github.com URLs.
+pub struct GitHubRepo;
+
+impl Enricher for GitHubRepo {
+    fn enrich(&self, url: &str) -> Option<Enrichment> {
+        let (host, path) = host_and_path(url);
+        if host != "github.com" {
+            return None;
+        }
+        let mut segments = path.split('/').filter(|s| !s.is_empty());
+        let owner = segments.next()?;
+        let name = segments.next()?.trim_end_matches(".git");
+        if name.is_empty() {
+            return None;
+        }
+        Some(Enrichment::GitHubRepo {
+            owner: owner.to_string(),
+            name: name.to_string(),
+        })
+    }
+}
+
+/// Extracts the paper id from arxiv.org abstract and PDF URLs.
+pub struct Arxiv;
+
+impl Enricher for Arxiv {
+    fn enrich(&self, url: &str) -> Option<Enrichment> {
+        let (host, path) = host_and_path(url);
+        if host != "arxiv.org" && host != "export.arxiv.org" {
+            return None;
+        }
+        let id = path
+            .strip_prefix("abs/")
+            .or_else(|| path.strip_prefix("pdf/"))?
+            .trim_end_matches(".pdf")
+            .trim_end_matches('/');
+        if id.is_empty() {
+            None
+        } else {
+            Some(Enrichment::ArxivId(id.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enrich() {
+        assert_eq!(
+            GitHubRepo.enrich("https://github.com/theduke/hackernews-api-rs"),
+            Some(Enrichment::GitHubRepo {
+                owner: "theduke".into(),
+                name: "hackernews-api-rs".into()
+            })
+        );
+        assert_eq!(
+            Arxiv.enrich("https://arxiv.org/pdf/1706.03762.pdf"),
+            Some(Enrichment::ArxivId("1706.03762".into()))
+        );
+        assert_eq!(GitHubRepo.enrich("https://example.com/a/b"), None);
+    }
+}
@@ -0,0 +1,132 @@
+use super::parse::ParseError;
+
+/// Errors returned by the client.
+///
+/// More variants may be added in future releases.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The HTTP request failed or returned an error status.
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// HN responded with an error status.
+    #[error("HTTP status {status} for {url}")]
+    Status { status: u16, url: String },
+    /// Reading the response body failed.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The page could not be parsed.
+    #[error("{0}")]
+    Parse(#[from] ParseError),
+    /// Logging in or signing up failed, or the account lacks permission.
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    /// HN refused to serve the request because of too many requests.
+    ///
+    /// `retry_hint` is set if HN said how long to back off.
+    #[error("Rate limited by HN")]
+    RateLimited {
+        retry_hint: Option<std::time::Duration>,
+    },
+    /// HN is down for maintenance or unreachable behind its CDN.
+    ///
+    /// `retry_hint` is set if the response said how long to wait.
+    #[error("HN is unavailable")]
+    Unavailable {
+        retry_hint: Option<std::time::Duration>,
+    },
+    /// A posting guard refused the action, see [crate::PostingGuards].
+    #[error("Action refused: {0}")]
+    Refused(String),
+    /// The requested item or page does not exist.
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// The page is private to another account, or requires logging in.
+    #[error("Access denied: {0}")]
+    Forbidden(String),
+    /// The requested item was deleted.
+    #[error("Item {0} was deleted")]
+    Deleted(String),
+    /// A checkpoint store failed.
+    #[error("Storage error: {0}")]
+    Storage(String),
+    /// The client configuration is invalid.
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+    /// The requested page lies past the end of the listing.
+    #[error("Page {page} is out of range")]
+    PageOutOfRange { page: u64 },
+    /// Robots.txt compliance is enabled and disallows the path.
+    #[error("Path '{path}' is disallowed by robots.txt")]
+    RobotsDisallowed { path: String },
+    /// The call did not complete within its deadline.
+    #[error("Request timed out after {after:?}")]
+    Timeout { after: std::time::Duration },
+    /// The response exceeded a configured size limit.
+    #[error("Response exceeds the limit of {limit} {unit}")]
+    TooLarge { limit: usize, unit: &'static str },
+    /// More redirects than [crate::RedirectPolicy::limited] allows.
+    #[error("More than {limit} redirects")]
+    TooManyRedirects { limit: usize },
+}
+
+impl Error {
+    /// Whether the failure is likely temporary, so that repeating the call
+    /// later may succeed.
+    ///
+    /// True for timeouts, connection failures, server errors and rate
+    /// limiting; false for eg. parse failures or rejected credentials.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Error::RateLimited { .. } | Error::Unavailable { .. })
+            || self.is_transient()
+    }
+
+    /// How long HN asked to wait before retrying, if it said.
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            Error::RateLimited { retry_hint }
+            | Error::Unavailable { retry_hint } => *retry_hint,
+            _ => None,
+        }
+    }
+
+    /// Failures the built-in [crate::RetryPolicy] retries.
+    ///
+    /// Rate limiting is left to the [crate::RateLimit] settings, and outages
+    /// usually outlast the backoff.
+    pub(crate) fn is_transient(&self) -> bool {
+        match self {
+            Error::Timeout { .. } | Error::Io(_) => true,
+            Error::Status { status, .. } => *status >= 500,
+            Error::Http(err) => {
+                err.is_timeout()
+                    || err.is_server_error()
+                    || (err.status().is_none() && err.is_http())
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_retryable() {
+        let hint = std::time::Duration::from_secs(30);
+        let limited = Error::RateLimited {
+            retry_hint: Some(hint),
+        };
+        assert!(limited.is_retryable());
+        assert_eq!(limited.retry_after(), Some(hint));
+        let unavailable = Error::Status {
+            status: 503,
+            url: String::new(),
+        };
+        assert!(unavailable.is_retryable());
+        assert_eq!(unavailable.retry_after(), None);
+        assert!(!Error::Auth("Bad login".into()).is_retryable());
+        assert!(!Error::NotFound("item 1".into()).is_retryable());
+    }
+}
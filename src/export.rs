@@ -0,0 +1,282 @@
+//! Export of fetched data for analysis in external tools.
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::{media, types::Post};
+
+/// What stories linked through a hub in a [StoryGraph] share.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Relation {
+    /// They link to the same article, see [crate::analytics::same_article].
+    SameUrl,
+    /// They link to the same site.
+    SameDomain,
+    /// They were submitted by the same user.
+    SameAuthor,
+}
+
+impl Relation {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::SameUrl => "same_url",
+            Self::SameDomain => "same_domain",
+            Self::SameAuthor => "same_author",
+        }
+    }
+
+    /// Prefix of hub ids, keeping them apart from story ids.
+    fn hub_prefix(self) -> &'static str {
+        match self {
+            Self::SameUrl => "url",
+            Self::SameDomain => "domain",
+            Self::SameAuthor => "user",
+        }
+    }
+}
+
+/// A story in a [StoryGraph].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoryNode {
+    pub id: String,
+    pub title: String,
+    /// Link target, empty for self posts.
+    pub url: String,
+    /// Submitter, empty for job postings.
+    pub username: String,
+    /// Host of the URL, without a `www.` prefix.
+    pub domain: String,
+}
+
+/// An article, site or user shared by several stories in a [StoryGraph].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HubNode {
+    /// The label, prefixed with `url:`, `domain:` or `user:`.
+    pub id: String,
+    pub relation: Relation,
+    /// The article URL without its scheme, the domain or the username.
+    pub label: String,
+}
+
+/// A link from a story to a hub, given by their ids.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoryEdge {
+    /// Id of the story.
+    pub source: String,
+    /// Id of the hub.
+    pub target: String,
+    pub relation: Relation,
+}
+
+/// Stories linked by shared URLs, domains and authors.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoryGraph {
+    pub nodes: Vec<StoryNode>,
+    pub hubs: Vec<HubNode>,
+    pub edges: Vec<StoryEdge>,
+}
+
+/// Build the graph of the given stories.
+///
+/// Stories sharing an article, a domain or an author are linked through
+/// a hub node standing for it, rather than with each other, so every story
+/// has at most three edges. Hubs are only added for properties at least
+/// two stories share. Posts with the same id are only included once.
+pub fn story_graph(posts: &[Post]) -> StoryGraph {
+    let mut seen = HashSet::new();
+    let nodes = posts
+        .iter()
+        .filter(|post| seen.insert(post.id.as_str()))
+        .map(|post| {
+            let url = post.url.clone().unwrap_or_default();
+            let domain = media::host_and_path(&url).0;
+            StoryNode {
+                id: post.id.clone(),
+                title: post.title.clone(),
+                url,
+                username: post.username.clone().unwrap_or_default(),
+                domain,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Ordered, to keep the output stable.
+    let mut shared: BTreeMap<(Relation, String), Vec<&str>> = BTreeMap::new();
+    for node in &nodes {
+        let mut share = |relation, label| {
+            shared
+                .entry((relation, label))
+                .or_default()
+                .push(node.id.as_str())
+        };
+        if !node.domain.is_empty() {
+            let (_, path) = media::host_and_path(&node.url);
+            let article =
+                format!("{}/{}", node.domain, path.trim_end_matches('/'));
+            share(Relation::SameUrl, article);
+            share(Relation::SameDomain, node.domain.clone());
+        }
+        if !node.username.is_empty() {
+            share(Relation::SameAuthor, node.username.clone());
+        }
+    }
+
+    let mut hubs = Vec::new();
+    let mut edges = Vec::new();
+    for ((relation, label), ids) in shared {
+        if ids.len() < 2 {
+            continue;
+        }
+        let hub = format!("{}:{}", relation.hub_prefix(), label);
+        edges.extend(ids.into_iter().map(|id| StoryEdge {
+            source: id.to_string(),
+            target: hub.clone(),
+            relation,
+        }));
+        hubs.push(HubNode {
+            id: hub,
+            relation,
+            label,
+        });
+    }
+
+    StoryGraph { nodes, hubs, edges }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&apos;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+impl StoryGraph {
+    /// Serialize the graph as GraphML, eg. for Gephi or NetworkX.
+    ///
+    /// Every node has a `kind` of `story` or, for hubs, the relation they
+    /// stand for. Stories carry `title`, `url`, `username` and `domain`,
+    /// hubs a `label`; edges carry their `relation` of `same_url`,
+    /// `same_domain` or `same_author`.
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        );
+        for key in &["kind", "title", "url", "username", "domain", "label"] {
+            out.push_str(&format!(
+                "  <key id=\"{0}\" for=\"node\" attr.name=\"{0}\" \
+                 attr.type=\"string\"/>\n",
+                key
+            ));
+        }
+        out.push_str(
+            "  <key id=\"relation\" for=\"edge\" attr.name=\"relation\" \
+             attr.type=\"string\"/>\n  <graph edgedefault=\"undirected\">\n",
+        );
+        let stories = self.nodes.iter().map(|node| {
+            (
+                &node.id,
+                vec![
+                    ("kind", "story"),
+                    ("title", node.title.as_str()),
+                    ("url", node.url.as_str()),
+                    ("username", node.username.as_str()),
+                    ("domain", node.domain.as_str()),
+                ],
+            )
+        });
+        let hubs = self.hubs.iter().map(|hub| {
+            (
+                &hub.id,
+                vec![
+                    ("kind", hub.relation.as_str()),
+                    ("label", hub.label.as_str()),
+                ],
+            )
+        });
+        for (id, data) in stories.chain(hubs) {
+            out.push_str(&format!("    <node id=\"{}\">\n", escape_xml(id)));
+            for (key, value) in data {
+                out.push_str(&format!(
+                    "      <data key=\"{}\">{}</data>\n",
+                    key,
+                    escape_xml(value)
+                ));
+            }
+            out.push_str("    </node>\n");
+        }
+        for edge in &self.edges {
+            out.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\">\n      \
+                 <data key=\"relation\">{}</data>\n    </edge>\n",
+                escape_xml(&edge.source),
+                escape_xml(&edge.target),
+                edge.relation.as_str()
+            ));
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_story_graph() {
+        let post = |id: &str, url: Option<&str>, username: &str| {
+            let mut post = Post::new(id, format!("Story {}", id));
+            post.url = url.map(String::from);
+            post.username = Some(username.into());
+            post
+        };
+        let posts = vec![
+            post("1", Some("https://example.com/a"), "alice"),
+            post("2", Some("http://www.example.com/a/"), "bob"),
+            post("3", Some("https://example.com/b"), "alice"),
+            post("4", None, "carol"),
+        ];
+        let graph = story_graph(&posts);
+        assert_eq!(graph.nodes.len(), 4);
+        let edges = graph
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str(), e.relation))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            edges,
+            vec![
+                ("1", "url:example.com/a", Relation::SameUrl),
+                ("2", "url:example.com/a", Relation::SameUrl),
+                ("1", "domain:example.com", Relation::SameDomain),
+                ("2", "domain:example.com", Relation::SameDomain),
+                ("3", "domain:example.com", Relation::SameDomain),
+                ("1", "user:alice", Relation::SameAuthor),
+                ("3", "user:alice", Relation::SameAuthor),
+            ]
+        );
+        let hubs = graph
+            .hubs
+            .iter()
+            .map(|hub| (hub.id.as_str(), hub.label.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hubs,
+            vec![
+                ("url:example.com/a", "example.com/a"),
+                ("domain:example.com", "example.com"),
+                ("user:alice", "alice"),
+            ]
+        );
+        let graphml = graph.to_graphml();
+        assert!(graphml.contains("<data key=\"relation\">same_url</data>"));
+        assert!(graphml.contains("<node id=\"4\">"));
+        assert!(graphml.contains("<node id=\"user:alice\">"));
+    }
+}
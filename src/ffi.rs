@@ -0,0 +1,106 @@
+//! C ABI for the read API, for embedding in mobile apps.
+//!
+//! Results are returned as JSON strings which must be released with
+//! [hn_string_free]. On failure the JSON is an object with a single `error`
+//! field. Build a linkable library with eg.
+//! `cargo rustc --release --features ffi --crate-type staticlib`.
+
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    ptr,
+};
+
+use super::{Client, Error};
+
+fn to_json<T: serde::Serialize>(res: Result<T, Error>) -> *mut c_char {
+    let json = res
+        .and_then(|value| {
+            serde_json::to_string(&value)
+                .map_err(|e| Error::Config(e.to_string()))
+        })
+        .unwrap_or_else(|err| {
+            serde_json::json!({ "error": err.to_string() }).to_string()
+        });
+    // JSON escapes control characters, so there are no interior nul bytes.
+    CString::new(json)
+        .map(CString::into_raw)
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Create a client with the default configuration.
+///
+/// Returns null if the HTTP client can not be initialized. Release the
+/// client with [hn_client_free].
+#[no_mangle]
+pub extern "C" fn hn_client_new() -> *mut Client {
+    match Client::try_new() {
+        Ok(client) => Box::into_raw(Box::new(client)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Release a client created by [hn_client_new].
+///
+/// # Safety
+///
+/// `client` must be null or a pointer returned by [hn_client_new] that was
+/// not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn hn_client_free(client: *mut Client) {
+    if !client.is_null() {
+        drop(Box::from_raw(client));
+    }
+}
+
+/// Get a page of the top posts as JSON.
+///
+/// # Safety
+///
+/// `client` must be a valid pointer returned by [hn_client_new].
+#[no_mangle]
+pub unsafe extern "C" fn hn_top_json(
+    client: *const Client,
+    page: u64,
+) -> *mut c_char {
+    let client = match client.as_ref() {
+        Some(client) => client,
+        None => return ptr::null_mut(),
+    };
+    to_json(client.top(page))
+}
+
+/// Get a post with its comments as JSON.
+///
+/// # Safety
+///
+/// `client` must be a valid pointer returned by [hn_client_new] and `id` a
+/// valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hn_item_json(
+    client: *const Client,
+    id: *const c_char,
+) -> *mut c_char {
+    let client = match client.as_ref() {
+        Some(client) => client,
+        None => return ptr::null_mut(),
+    };
+    if id.is_null() {
+        return ptr::null_mut();
+    }
+    let id = CStr::from_ptr(id).to_string_lossy();
+    to_json(client.submission(&id))
+}
+
+/// Release a string returned by this library.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that was not freed
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn hn_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
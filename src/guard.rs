@@ -0,0 +1,307 @@
+use std::{
+    collections::{hash_map::DefaultHasher, VecDeque},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use super::{error::Error, types::VoteAction};
+
+/// Limits on the mutating API of an [crate::AuthenticatedClient].
+///
+/// Enabled by default with limits well within what a human user does, so
+/// bots built on this crate stay within HN norms. Actions exceeding a limit
+/// fail with [Error::Refused] without contacting HN.
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct PostingGuards {
+    /// Maximum up- and downvotes per hour.
+    pub max_votes_per_hour: Option<u32>,
+    /// Maximum downvotes per hour, to refuse mass downvoting.
+    pub max_downvotes_per_hour: Option<u32>,
+    /// Maximum polls and other submissions per hour.
+    pub max_submissions_per_hour: Option<u32>,
+    /// Refuse submitting the same text twice.
+    pub reject_duplicates: bool,
+}
+
+impl PostingGuards {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Turn all guards off.
+    pub fn disabled() -> Self {
+        Self {
+            max_votes_per_hour: None,
+            max_downvotes_per_hour: None,
+            max_submissions_per_hour: None,
+            reject_duplicates: false,
+        }
+    }
+
+    pub fn max_votes_per_hour(mut self, max: Option<u32>) -> Self {
+        self.max_votes_per_hour = max;
+        self
+    }
+
+    pub fn max_downvotes_per_hour(mut self, max: Option<u32>) -> Self {
+        self.max_downvotes_per_hour = max;
+        self
+    }
+
+    pub fn max_submissions_per_hour(mut self, max: Option<u32>) -> Self {
+        self.max_submissions_per_hour = max;
+        self
+    }
+
+    pub fn reject_duplicates(mut self, reject: bool) -> Self {
+        self.reject_duplicates = reject;
+        self
+    }
+}
+
+impl Default for PostingGuards {
+    fn default() -> Self {
+        Self {
+            max_votes_per_hour: Some(100),
+            max_downvotes_per_hour: Some(10),
+            max_submissions_per_hour: Some(5),
+            reject_duplicates: true,
+        }
+    }
+}
+
+const WINDOW: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Default)]
+struct State {
+    votes: VecDeque<Instant>,
+    downvotes: VecDeque<Instant>,
+    submissions: VecDeque<Instant>,
+    /// Hashes of submitted texts, oldest first.
+    texts: VecDeque<(Instant, u64)>,
+}
+
+/// Enforces [PostingGuards] for one account.
+#[derive(Debug)]
+pub(crate) struct Guard {
+    config: PostingGuards,
+    state: Mutex<State>,
+}
+
+/// Fail if `max` actions happened in the last hour.
+fn admit(
+    window: &mut VecDeque<Instant>,
+    max: Option<u32>,
+    what: &str,
+) -> Result<(), Error> {
+    while window.front().is_some_and(|t| t.elapsed() >= WINDOW) {
+        window.pop_front();
+    }
+    if let Some(max) = max {
+        if window.len() >= max as usize {
+            return Err(Error::Refused(format!(
+                "More than {} {} per hour",
+                max, what
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Hash text fields, ignoring case and differences in whitespace.
+///
+/// Fields are hashed separately, so moving text from one field to the next
+/// makes a different submission.
+fn hash_fields(fields: &[&str]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for field in fields {
+        field
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+            .hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+impl Guard {
+    pub fn new(config: PostingGuards) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Take a slot for a vote, before sending it.
+    ///
+    /// Concurrent votes through clones of the client each take their own
+    /// slot, so they can not exceed the limits together.
+    pub fn reserve_vote(
+        &self,
+        action: &VoteAction,
+    ) -> Result<Reservation<'_>, Error> {
+        let mut state = self.lock();
+        admit(&mut state.votes, self.config.max_votes_per_hour, "votes")?;
+        let downvote = !action.is_upvote();
+        if downvote {
+            admit(
+                &mut state.downvotes,
+                self.config.max_downvotes_per_hour,
+                "downvotes",
+            )?;
+        }
+        let at = Instant::now();
+        state.votes.push_back(at);
+        if downvote {
+            state.downvotes.push_back(at);
+        }
+        Ok(self.reservation(at, Taken::Vote { downvote }))
+    }
+
+    /// Take a slot for a submission, identified by its text fields, before
+    /// sending it.
+    pub fn reserve_submission(
+        &self,
+        fields: &[&str],
+    ) -> Result<Reservation<'_>, Error> {
+        let mut state = self.lock();
+        admit(
+            &mut state.submissions,
+            self.config.max_submissions_per_hour,
+            "submissions",
+        )?;
+        while state
+            .texts
+            .front()
+            .is_some_and(|(t, _)| t.elapsed() >= WINDOW)
+        {
+            state.texts.pop_front();
+        }
+        let hash = hash_fields(fields);
+        if self.config.reject_duplicates
+            && state.texts.iter().any(|(_, h)| *h == hash)
+        {
+            return Err(Error::Refused("Duplicate submission".into()));
+        }
+        let at = Instant::now();
+        state.submissions.push_back(at);
+        state.texts.push_back((at, hash));
+        Ok(self.reservation(at, Taken::Submission { hash }))
+    }
+
+    fn reservation(&self, at: Instant, taken: Taken) -> Reservation<'_> {
+        Reservation {
+            guard: self,
+            at,
+            taken,
+            kept: false,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug)]
+enum Taken {
+    Vote { downvote: bool },
+    Submission { hash: u64 },
+}
+
+/// Remove the entry taken at `at`.
+fn release(window: &mut VecDeque<Instant>, at: Instant) {
+    if let Some(i) = window.iter().rposition(|t| *t == at) {
+        window.remove(i);
+    }
+}
+
+/// A slot taken by a guarded action while it is sent.
+///
+/// Dropping it releases the slot, so failed actions can be retried; call
+/// [Reservation::keep] once HN accepted the action.
+#[must_use]
+#[derive(Debug)]
+pub(crate) struct Reservation<'a> {
+    guard: &'a Guard,
+    at: Instant,
+    taken: Taken,
+    kept: bool,
+}
+
+impl Reservation<'_> {
+    /// Count the action against the limits.
+    pub fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if self.kept {
+            return;
+        }
+        let mut state = self.guard.lock();
+        match self.taken {
+            Taken::Vote { downvote } => {
+                release(&mut state.votes, self.at);
+                if downvote {
+                    release(&mut state.downvotes, self.at);
+                }
+            }
+            Taken::Submission { hash } => {
+                release(&mut state.submissions, self.at);
+                let at = self.at;
+                if let Some(i) =
+                    state.texts.iter().rposition(|e| *e == (at, hash))
+                {
+                    state.texts.remove(i);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_posting_guards() {
+        let guard = Guard::new(
+            PostingGuards::new()
+                .max_votes_per_hour(Some(3))
+                .max_downvotes_per_hour(Some(1)),
+        );
+        let up = VoteAction::Upvote("vote?id=1&how=up".into());
+        let down = VoteAction::Downvote("vote?id=2&how=un".into());
+        // Slots of failed votes are released, so they can be retried.
+        drop(guard.reserve_vote(&down).unwrap());
+        guard.reserve_vote(&down).unwrap().keep();
+        assert!(matches!(guard.reserve_vote(&down), Err(Error::Refused(_))));
+        // A pending vote holds its slot until it is kept or released.
+        let pending = guard.reserve_vote(&up).unwrap();
+        guard.reserve_vote(&up).unwrap().keep();
+        assert!(matches!(guard.reserve_vote(&up), Err(Error::Refused(_))));
+        drop(pending);
+        guard.reserve_vote(&up).unwrap().keep();
+        assert!(matches!(guard.reserve_vote(&up), Err(Error::Refused(_))));
+
+        let pending = guard.reserve_submission(&["Poll", "a  b"]).unwrap();
+        assert!(matches!(
+            guard.reserve_submission(&["poll", "a b"]),
+            Err(Error::Refused(_))
+        ));
+        drop(pending);
+        guard.reserve_submission(&["Poll", "a  b"]).unwrap().keep();
+        assert!(matches!(
+            guard.reserve_submission(&["poll", "a b"]),
+            Err(Error::Refused(_))
+        ));
+        guard.reserve_submission(&["poll a", "b"]).unwrap().keep();
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use super::{
+    error::Error,
+    transport::{Request, Response},
+};
+
+type RequestHook = Arc<dyn Fn(&mut Request) + Send + Sync>;
+type InterceptHook = Arc<dyn Fn(&Request) -> Option<Response> + Send + Sync>;
+type ResponseHook =
+    Arc<dyn Fn(&Request, Result<&Response, &Error>) + Send + Sync>;
+
+/// Callbacks registered with [crate::ClientBuilder::on_request],
+/// [crate::ClientBuilder::intercept] and [crate::ClientBuilder::on_response].
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    on_request: Vec<RequestHook>,
+    intercept: Vec<InterceptHook>,
+    on_response: Vec<ResponseHook>,
+}
+
+impl Hooks {
+    pub fn add_request(&mut self, hook: RequestHook) {
+        self.on_request.push(hook);
+    }
+
+    pub fn add_intercept(&mut self, hook: InterceptHook) {
+        self.intercept.push(hook);
+    }
+
+    pub fn add_response(&mut self, hook: ResponseHook) {
+        self.on_response.push(hook);
+    }
+
+    pub fn request(&self, request: &mut Request) {
+        for hook in &self.on_request {
+            hook(request);
+        }
+    }
+
+    /// The response of the first intercept hook answering the request.
+    pub fn intercept(&self, request: &Request) -> Option<Response> {
+        self.intercept.iter().find_map(|hook| hook(request))
+    }
+
+    pub fn response(&self, request: &Request, res: Result<&Response, &Error>) {
+        for hook in &self.on_response {
+            hook(request, res);
+        }
+    }
+}
+
+impl std::fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Hooks")
+            .field("on_request", &self.on_request.len())
+            .field("intercept", &self.intercept.len())
+            .field("on_response", &self.on_response.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_support::list_fixture, transport::MockTransport, Client,
+        RetryPolicy,
+    };
+
+    #[test]
+    fn test_intercept() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mock = Arc::new(MockTransport::new());
+        let responses = Arc::new(AtomicUsize::new(0));
+        let counter = responses.clone();
+        let client = Client::builder()
+            .base_url("http://hn.test")
+            .transport(mock.clone())
+            .intercept(|request| {
+                let status = match request.url.as_str() {
+                    "http://hn.test/news?p=1" => 200,
+                    "http://hn.test/news?p=2" => 503,
+                    _ => return None,
+                };
+                Some(Response {
+                    status,
+                    url: request.url.clone(),
+                    redirects: Vec::new(),
+                    headers: Vec::new(),
+                    body: list_fixture(&[1]),
+                })
+            })
+            .on_response(move |_, _| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+
+        assert_eq!(client.top(1).unwrap().items[0].id, "1");
+        assert!(matches!(
+            client.top(2),
+            Err(Error::Status { status: 503, .. })
+        ));
+        assert!(mock.requests().is_empty());
+        assert_eq!(responses.load(Ordering::SeqCst), 2);
+        assert!(client.user("pg").is_err());
+        assert_eq!(mock.requests().len(), 1);
+    }
+}
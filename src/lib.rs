@@ -1,58 +1,1405 @@
-mod parse;
+use std::sync::Arc;
+
+pub mod analytics;
+mod batch;
+mod cache;
+pub mod checkpoint;
+#[cfg(feature = "keyring")]
+pub mod credentials;
+pub mod diff;
+pub mod enrich;
+mod error;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod guard;
+mod hooks;
+mod media;
+pub mod metrics;
+mod options;
+pub mod parse;
+mod rate_limit;
+mod redirect;
+mod retry;
+mod robots;
+mod semaphore;
+#[cfg(test)]
+mod test_support;
+mod transform;
+pub mod transport;
 mod types;
+#[cfg(feature = "wayback")]
+pub mod wayback;
+
+const DEFAULT_BASE_URL: &str = "https://news.ycombinator.com/";
+
+/// How long fetched profiles are reused by [Client::users].
+const DEFAULT_PROFILE_TTL: std::time::Duration =
+    std::time::Duration::from_secs(60 * 60);
+
+/// Number of threads fetching profiles in [Client::users].
+const PROFILE_WORKERS: usize = 4;
+
+/// Body of the page HN serves instead of the requested one when fetching
+/// too fast.
+const SOFT_BAN_MESSAGE: &str =
+    "Sorry, we're not able to serve your requests this quickly.";
+
+/// Body of the page HN serves instead of another account's private pages.
+const PRIVATE_PAGE_MESSAGE: &str = "Can't display that.";
+
+/// Part of the page HN serves while down for maintenance.
+const DOWNTIME_MESSAGE: &str = "Sorry for the inconvenience";
+
+const DEFAULT_USER_AGENT: &str = concat!(
+    "hackernews-api-rs/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/theduke/hackernews-api-rs)"
+);
+
+pub use batch::BatchReport;
+pub use error::Error;
+pub use guard::PostingGuards;
+pub use media::{classify_url, MediaKind};
+pub use options::{ListOptions, RequestOptions};
+pub use rate_limit::RateLimit;
+pub use redirect::RedirectPolicy;
+pub use retry::RetryPolicy;
+pub use robots::RobotsPolicy;
+pub use transform::TextTransformer;
+pub use types::{
+    Capabilities, Comment, Cursor, Fetched, Health, Item, MoreComments,
+    Ordered, Page, Post, PostActions, PostKind, PostMeta, UnavailableReason,
+    User, VoteAction, VoteAvailability,
+};
+
+/// Builder for configuring a [Client].
+#[derive(Clone, Debug)]
+pub struct ClientBuilder {
+    user_agent: String,
+    base_url: String,
+    timeout: Option<std::time::Duration>,
+    connect_timeout: Option<std::time::Duration>,
+    proxy: Option<String>,
+    proxy_auth: Option<(String, String)>,
+    cookie_store: bool,
+    gzip: bool,
+    http2_prior_knowledge: bool,
+    max_idle_per_host: Option<usize>,
+    local_address: Option<std::net::IpAddr>,
+    redirect_policy: RedirectPolicy,
+    http_client: Option<reqwest::Client>,
+    transport: Option<transport::Shared>,
+    hooks: hooks::Hooks,
+    metrics: Option<metrics::Shared>,
+    robots: RobotsSetting,
+    limits: Limits,
+    retry: RetryPolicy,
+    rate_limit: Option<RateLimit>,
+    max_concurrent_requests: Option<usize>,
+    profile_ttl: std::time::Duration,
+    transformer: Option<transform::CachedTransformer>,
+}
+
+/// Size limits for responses.
+#[derive(Clone, Copy, Debug, Default)]
+struct Limits {
+    max_response_bytes: Option<usize>,
+    max_html_nodes: Option<usize>,
+}
+
+/// Robots.txt compliance setting.
+#[derive(Clone, Debug)]
+enum RobotsSetting {
+    Ignore,
+    Fetch,
+    Policy(RobotsPolicy),
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            timeout: None,
+            connect_timeout: None,
+            proxy: None,
+            proxy_auth: None,
+            cookie_store: true,
+            gzip: true,
+            http2_prior_knowledge: false,
+            max_idle_per_host: None,
+            local_address: None,
+            redirect_policy: RedirectPolicy::default(),
+            http_client: None,
+            transport: None,
+            hooks: hooks::Hooks::default(),
+            metrics: None,
+            robots: RobotsSetting::Ignore,
+            limits: Limits::default(),
+            retry: RetryPolicy::none(),
+            rate_limit: None,
+            max_concurrent_requests: None,
+            profile_ttl: DEFAULT_PROFILE_TTL,
+            transformer: None,
+        }
+    }
+
+    /// Replace the User-Agent header.
+    ///
+    /// Defaults to `hackernews-api-rs/<version> (+<repository url>)`.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Identify the consuming application by appending its name to the
+    /// User-Agent, eg. `my-reader/1.0`.
+    pub fn application(mut self, name: &str) -> Self {
+        self.user_agent.push(' ');
+        self.user_agent.push_str(name);
+        self
+    }
+
+    /// Site to talk to, eg. a mirror or a local test server.
+    ///
+    /// Applies to all requests, including logging in and voting.
+    /// Defaults to `https://news.ycombinator.com/`.
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        if !self.base_url.ends_with('/') {
+            self.base_url.push('/');
+        }
+        self
+    }
+
+    /// Total timeout for each request.
+    ///
+    /// Defaults to the reqwest default of 30 seconds.
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for establishing the connection.
+    ///
+    /// Individual calls can be bounded further with
+    /// [RequestOptions::timeout].
+    pub fn connect_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Retry transient failures of page fetches and logins.
+    ///
+    /// Disabled by default.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Throttle page fetches and votes.
+    ///
+    /// Applies to all requests of the client, including retries.
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Limit the number of requests in flight at once.
+    ///
+    /// Applies across all threads sharing the client or its clones; further
+    /// requests wait for a free slot.
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = Some(max);
+        self
+    }
+
+    /// How long profiles fetched by [Client::user] and [Client::users] are
+    /// reused by [Client::users].
+    ///
+    /// Defaults to one hour.
+    pub fn profile_cache_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.profile_ttl = ttl;
+        self
+    }
+
+    /// Send all requests through a proxy, eg. `http://proxy.local:3128`.
+    ///
+    /// `socks5://` and `socks5h://` proxies require the `socks` feature.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Credentials for the proxy set with [ClientBuilder::proxy].
+    pub fn proxy_auth(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.proxy_auth = Some((username.into(), password.into()));
+        self
+    }
 
-use failure::Error as DynErr;
+    /// Keep cookies between requests.
+    ///
+    /// Enabled by default, and required for [AuthenticatedClient].
+    pub fn cookie_store(mut self, enabled: bool) -> Self {
+        self.cookie_store = enabled;
+        self
+    }
+
+    /// Request gzip-compressed responses and decompress them transparently.
+    ///
+    /// Enabled by default. Brotli is not supported by the HTTP client.
+    pub fn gzip(mut self, enabled: bool) -> Self {
+        self.gzip = enabled;
+        self
+    }
+
+    /// Speak HTTP/2 right away instead of negotiating it.
+    ///
+    /// Fails against servers that only support HTTP/1.1.
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// Maximum number of idle connections kept open per host for reuse.
+    pub fn max_idle_per_host(mut self, max: usize) -> Self {
+        self.max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Send requests from the given local IP address.
+    ///
+    /// Useful on machines with several egress addresses, eg. to move off
+    /// one that HN rate limits.
+    pub fn local_address(mut self, address: std::net::IpAddr) -> Self {
+        self.local_address = Some(address);
+        self
+    }
+
+    /// Set how redirects are handled.
+    ///
+    /// Followed redirects are recorded in [Fetched::redirects]. Defaults to
+    /// following up to 10 redirects.
+    ///
+    /// Not applied to a client from [ClientBuilder::http_client] unless
+    /// that client is built with `reqwest::RedirectPolicy::none()`.
+    pub fn redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect_policy = policy;
+        self
+    }
+
+    /// Use a preconfigured reqwest client instead of building one.
+    ///
+    /// The User-Agent, timeout, proxy and cookie settings of this builder
+    /// are ignored; configure them on the reqwest client instead.
+    /// [AuthenticatedClient] requires the cookie store to be enabled.
+    ///
+    /// **The redirect policy is bypassed unless the client is built with
+    /// `reqwest::RedirectPolicy::none()`.** reqwest follows redirects on
+    /// its own by default, so [ClientBuilder::redirect_policy] is ignored,
+    /// [Fetched::redirects] stays empty and [Fetched::location] unset, and
+    /// logins are only recognized by the page they end up on.
+    ///
+    /// **Per-call timeouts fall back to helper threads.** The client can
+    /// not be rebuilt with another timeout, so a call with
+    /// [RequestOptions::timeout] sends its request from a separate thread
+    /// and stops waiting when the timeout passes; the request itself keeps
+    /// running until reqwest gives up. **Once 16 of these threads are
+    /// running, further calls with a timeout fail immediately** with
+    /// [Error::Timeout], without sending anything. Clones of the client
+    /// share the 16 threads.
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
 
-const USER_AGENT: &'static str =
-    "Mozilla/5.0 (X11; Linux x86_64; rv:69.0) Gecko/20100101 Firefox/69.0";
+    /// Send requests through a custom [transport::Transport], eg. a
+    /// [transport::MockTransport] in tests.
+    ///
+    /// All HTTP settings of this builder are ignored.
+    pub fn transport(
+        mut self,
+        transport: Arc<dyn transport::Transport>,
+    ) -> Self {
+        self.transport = Some(transport::Shared(transport));
+        self
+    }
 
-pub use types::{Comment, Post, VoteAction};
+    /// Call `hook` before every request, eg. to log it or add headers.
+    ///
+    /// Hooks run in the order they were added, on every attempt.
+    pub fn on_request(
+        mut self,
+        hook: impl Fn(&mut transport::Request) + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.add_request(Arc::new(hook));
+        self
+    }
+
+    /// Let `hook` answer requests instead of sending them, eg. from a
+    /// cache.
+    ///
+    /// Runs after the [ClientBuilder::on_request] hooks; the first hook
+    /// returning a response wins. Intercepted responses skip the transport,
+    /// the concurrency limit and the metrics, but are otherwise handled
+    /// like received ones, including the [ClientBuilder::on_response]
+    /// hooks.
+    pub fn intercept(
+        mut self,
+        hook: impl Fn(&transport::Request) -> Option<transport::Response>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.hooks.add_intercept(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` after every request with its outcome.
+    pub fn on_response(
+        mut self,
+        hook: impl Fn(&transport::Request, Result<&transport::Response, &Error>)
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.hooks.add_response(Arc::new(hook));
+        self
+    }
+
+    /// Report request counts, latencies, retries and parse failures.
+    pub fn metrics(mut self, metrics: Arc<dyn metrics::Metrics>) -> Self {
+        self.metrics = Some(metrics::Shared(metrics));
+        self
+    }
+
+    /// Enable robots.txt compliance.
+    ///
+    /// The site's robots.txt is fetched when building the client.
+    /// Reads of disallowed paths then fail with [Error::RobotsDisallowed],
+    /// and consecutive reads are spaced by the crawl delay.
+    ///
+    /// Explicit user actions (login, voting, creating polls and checking
+    /// [AuthenticatedClient::capabilities]) are not crawling and are not
+    /// affected.
+    pub fn respect_robots_txt(mut self, enabled: bool) -> Self {
+        self.robots = if enabled {
+            RobotsSetting::Fetch
+        } else {
+            RobotsSetting::Ignore
+        };
+        self
+    }
+
+    /// Enable robots.txt compliance with a known policy instead of fetching
+    /// it.
+    pub fn robots_policy(mut self, policy: RobotsPolicy) -> Self {
+        self.robots = RobotsSetting::Policy(policy);
+        self
+    }
+
+    /// Abort reading responses larger than `bytes` with [Error::TooLarge].
+    pub fn max_response_size(mut self, bytes: usize) -> Self {
+        self.limits.max_response_bytes = Some(bytes);
+        self
+    }
+
+    /// Refuse to parse documents with more than `nodes` HTML elements,
+    /// failing with [Error::TooLarge].
+    ///
+    /// Elements are counted by their start tags before parsing, so
+    /// oversized documents never take up memory as a tree. Combine with
+    /// [ClientBuilder::max_response_size] to bound the raw HTML as well.
+    pub fn max_html_nodes(mut self, nodes: usize) -> Self {
+        self.limits.max_html_nodes = Some(nodes);
+        self
+    }
+
+    /// Rewrite the content of all parsed comments.
+    ///
+    /// Results are cached per comment id, so unchanged comments are only
+    /// transformed once.
+    pub fn text_transformer(
+        mut self,
+        transformer: Arc<dyn TextTransformer>,
+    ) -> Self {
+        self.transformer = Some(transform::CachedTransformer::new(transformer));
+        self
+    }
+
+    /// Build a reqwest client, with `timeout` replacing the configured
+    /// one.
+    fn build_http_client(
+        &self,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<reqwest::Client, Error> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        let user_agent =
+            reqwest::header::HeaderValue::from_str(&self.user_agent)
+                .map_err(|_| Error::Config("Invalid User-Agent".into()))?;
+        headers.insert(reqwest::header::USER_AGENT, user_agent);
+        let mut builder = reqwest::Client::builder()
+            // Cookies are kept by the transport, so that clients built for
+            // different timeouts share them.
+            .cookie_store(false)
+            .gzip(self.gzip)
+            // Redirects are followed by the transport, to record them.
+            .redirect(reqwest::RedirectPolicy::none())
+            .default_headers(headers);
+        // Prefer rustls if both backends are compiled in.
+        #[cfg(feature = "rustls-tls")]
+        {
+            builder = builder.use_rustls_tls();
+        }
+        if let Some(timeout) = timeout.or(self.timeout) {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.h2_prior_knowledge();
+        }
+        if let Some(max) = self.max_idle_per_host {
+            builder = builder.max_idle_per_host(max);
+        }
+        if let Some(address) = self.local_address {
+            builder = builder.local_address(address);
+        }
+        if let Some(url) = &self.proxy {
+            let mut proxy = reqwest::Proxy::all(url.as_str())?;
+            if let Some((username, password)) = &self.proxy_auth {
+                proxy = proxy.basic_auth(username, password);
+            }
+            builder = builder.proxy(proxy);
+        }
+        let inner = builder.build()?;
+        Ok(inner)
+    }
+
+    /// Build the client and log in with it.
+    ///
+    /// See [AuthenticatedClient::login_with].
+    pub fn login(
+        self,
+        username: &str,
+        password: &str,
+    ) -> Result<AuthenticatedClient, Error> {
+        AuthenticatedClient::login_with(self.build()?, username, password)
+    }
+
+    pub fn build(self) -> Result<Client, Error> {
+        let transport: Arc<dyn transport::Transport> = match &self.transport {
+            Some(transport) => transport.0.clone(),
+            None => match self.http_client.clone() {
+                Some(client) => Arc::new(transport::ReqwestTransport::new(
+                    client,
+                    self.limits.max_response_bytes,
+                    self.redirect_policy.clone(),
+                )),
+                None => {
+                    let builder = self.clone();
+                    Arc::new(transport::ReqwestTransport::with_builder(
+                        Box::new(move |timeout| {
+                            builder.build_http_client(timeout)
+                        }),
+                        self.cookie_store,
+                        self.limits.max_response_bytes,
+                        self.redirect_policy.clone(),
+                    )?)
+                }
+            },
+        };
+
+        let mut client = Client {
+            transport,
+            hooks: self.hooks,
+            metrics: self.metrics.map(|metrics| metrics.0),
+            base_url: self.base_url,
+            robots: None,
+            limits: self.limits,
+            retry: self.retry,
+            rate_limiter: self
+                .rate_limit
+                .map(|limit| Arc::new(rate_limit::RateLimiter::new(limit))),
+            concurrency: self
+                .max_concurrent_requests
+                .map(|max| Arc::new(semaphore::Semaphore::new(max))),
+            profiles: Arc::new(cache::TtlCache::new(self.profile_ttl)),
+            transformer: self.transformer,
+            deadlines: Arc::new(Deadlines::default()),
+        };
+
+        let policy = match self.robots {
+            RobotsSetting::Ignore => None,
+            RobotsSetting::Fetch => {
+                let request = transport::Request::get(client.url("robots.txt"));
+                let txt = client.send(request, None)?.body;
+                Some(RobotsPolicy::parse(&txt))
+            }
+            RobotsSetting::Policy(policy) => Some(policy),
+        };
+        client.robots =
+            policy.map(|policy| Arc::new(robots::Robots::new(policy)));
+
+        Ok(client)
+    }
+}
 
 /// Unauthenticated Hackernews client.
 ///
 /// See [AuthenticatedClient] for authenticated actions.
+///
+/// The client is `Send + Sync` and cheap to clone. Clones share the
+/// connection pool, cookies, rate limiter and robots.txt crawl delay, so a
+/// single client can serve multiple threads.
+#[derive(Clone)]
 pub struct Client {
-    inner: reqwest::Client,
+    transport: Arc<dyn transport::Transport>,
+    hooks: hooks::Hooks,
+    metrics: Option<Arc<dyn metrics::Metrics>>,
+    base_url: String,
+    robots: Option<Arc<robots::Robots>>,
+    limits: Limits,
+    retry: RetryPolicy,
+    rate_limiter: Option<Arc<rate_limit::RateLimiter>>,
+    concurrency: Option<Arc<semaphore::Semaphore>>,
+    profiles: Arc<cache::TtlCache<Fetched<User>>>,
+    transformer: Option<transform::CachedTransformer>,
+    deadlines: Arc<Deadlines>,
 }
 
 impl Client {
+    /// Create a client with the default configuration.
+    ///
+    /// Fails only if the HTTP client can not be initialized, eg. because
+    /// the TLS backend is unavailable.
+    pub fn try_new() -> Result<Self, Error> {
+        ClientBuilder::new().build()
+    }
+
+    /// Create a client with the default configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client can not be initialized.
+    #[deprecated(note = "Use Client::try_new or Client::builder instead")]
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert("user-agent", USER_AGENT.parse().unwrap());
-        let inner = reqwest::Client::builder()
-            .cookie_store(true)
-            .default_headers(headers)
-            .build()
-            .unwrap();
-        Self { inner }
+        Self::try_new().expect("Could not initialize HTTP client")
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Create a client reusing an existing reqwest client, eg. to share its
+    /// connection pool.
+    ///
+    /// See [ClientBuilder::http_client] for further configuration.
+    pub fn with_http_client(client: reqwest::Client) -> Result<Self, Error> {
+        ClientBuilder::new().http_client(client).build()
     }
 
-    fn get_html(&self, path: &str) -> Result<String, reqwest::Error> {
-        let url = format!("https://news.ycombinator.com/{}", path);
-        self.inner.get(&url).send()?.error_for_status()?.text()
+    /// Absolute URL of a path relative to the site root.
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
     }
 
-    fn get_dom(&self, path: &str) -> Result<scraper::Html, DynErr> {
-        let html = self.get_html(path)?;
-        Ok(scraper::Html::parse_document(&html))
+    /// URL of a post's discussion page, on the configured base URL.
+    pub fn discussion_url(&self, post: &Post) -> String {
+        types::item_url(&self.base_url, &post.id)
+    }
+
+    /// URL of a comment's own page, on the configured base URL.
+    pub fn permalink(&self, comment: &Comment) -> String {
+        types::item_url(&self.base_url, &comment.id)
+    }
+
+    fn get_html(
+        &self,
+        path: &str,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<String>, Error> {
+        let url = self.url(path);
+        let mut attempt = 0;
+        let mut latency = std::time::Duration::default();
+        // Retries belong to the same operation.
+        let correlation_id = opts
+            .correlation_id
+            .clone()
+            .unwrap_or_else(transport::new_correlation_id);
+        let retry = opts.retry.as_ref().unwrap_or(&self.retry);
+        let res = retry.run(|| {
+            attempt += 1;
+            if attempt > 1 {
+                opts.retried();
+                if let Some(metrics) = &self.metrics {
+                    metrics.retry(metrics::endpoint(&url), attempt);
+                }
+            }
+            if let (Some(robots), false) = (&self.robots, opts.user_action) {
+                robots.acquire(path)?;
+            }
+            self.throttle();
+            let mut request = transport::Request::get(url.as_str());
+            request.headers.extend(opts.headers.iter().cloned());
+            request.correlation_id = Some(correlation_id.clone());
+            let started = std::time::Instant::now();
+            let res = self.send(request, opts.timeout)?;
+            latency = started.elapsed();
+            Ok(res)
+        })?;
+        // Pages that need a login redirect to the login form. The final URL
+        // is checked too, as clients from ClientBuilder::http_client may
+        // follow redirects without recording them.
+        let to_login = (metrics::endpoint(&res.url) == "login"
+            && metrics::endpoint(&url) != "login")
+            || res.location().is_some_and(|l| l.starts_with("login"));
+        if to_login || res.body.trim() == PRIVATE_PAGE_MESSAGE {
+            return Err(Error::Forbidden(path.to_string()));
+        }
+        Ok(Fetched {
+            location: res.location().map(str::to_string),
+            size: res.body.len(),
+            value: res.body,
+            fetched_at: std::time::SystemTime::now(),
+            source_url: res.url,
+            status: res.status,
+            redirects: res.redirects,
+            latency,
+        })
+    }
+
+    /// Send a request, mapping error statuses and HN's throttling page to
+    /// errors.
+    fn send(
+        &self,
+        mut request: transport::Request,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<transport::Response, Error> {
+        if request.correlation_id.is_none() {
+            request.correlation_id = Some(transport::new_correlation_id());
+        }
+        request.timeout = timeout;
+        self.hooks.request(&mut request);
+        if let Some(res) = self.hooks.intercept(&request) {
+            self.hooks.response(&request, Ok(&res));
+            return self.check_response(res);
+        }
+        let permit = self.concurrency.as_ref().map(|sem| sem.acquire());
+        let start = std::time::Instant::now();
+        let res = match request.timeout {
+            Some(timeout) if !self.transport.enforces_timeout() => {
+                let transport = self.transport.clone();
+                let request = request.clone();
+                // The slot stays taken until the request finishes, even
+                // if the deadline passes first.
+                self.deadlines.run(timeout, move || {
+                    let res = transport.send(&request);
+                    drop(permit);
+                    res
+                })
+            }
+            _ => {
+                let res = self.transport.send(&request);
+                drop(permit);
+                res
+            }
+        };
+        self.hooks.response(&request, res.as_ref());
+        if let Some(metrics) = &self.metrics {
+            metrics.request(
+                metrics::endpoint(&request.url),
+                res.as_ref().ok().map(|res| res.status),
+                start.elapsed(),
+            );
+        }
+        self.check_response(res?)
+    }
+
+    /// Map error statuses and HN's throttling and downtime pages to errors.
+    fn check_response(
+        &self,
+        res: transport::Response,
+    ) -> Result<transport::Response, Error> {
+        let retry_hint = res
+            .header("retry-after")
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(std::time::Duration::from_secs);
+        if res.status == 429 {
+            return Err(Error::RateLimited { retry_hint });
+        }
+        // Cloudflare uses 52x statuses when the origin is unreachable.
+        let unavailable = (520..=527).contains(&res.status)
+            || (res.status >= 500
+                && res.body.to_lowercase().contains("cloudflare"))
+            || (res.body.len() < 8192 && res.body.contains(DOWNTIME_MESSAGE));
+        if unavailable {
+            return Err(Error::Unavailable { retry_hint });
+        }
+        if res.status >= 400 {
+            return Err(Error::Status {
+                status: res.status,
+                url: res.url,
+            });
+        }
+        if let Some(max) = self.limits.max_response_bytes {
+            if res.body.len() > max {
+                return Err(Error::TooLarge {
+                    limit: max,
+                    unit: "bytes",
+                });
+            }
+        }
+        // HN answers with a 200 status when it throttles a client.
+        if res.body.len() < 1024 && res.body.contains(SOFT_BAN_MESSAGE) {
+            return Err(Error::RateLimited { retry_hint: None });
+        }
+        Ok(res)
+    }
+
+    /// Fetch `path` once, without retries, and classify the outcome.
+    fn probe(&self, path: &str) -> Result<Fetched<String>, Health> {
+        let opts = RequestOptions::new().retry(RetryPolicy::none());
+        self.get_html(path, &opts).map_err(|err| match err {
+            Error::RateLimited { retry_hint } => {
+                Health::RateLimited { retry_hint }
+            }
+            Error::Status { status: 403, .. } => Health::Blocked,
+            _ => Health::Down,
+        })
+    }
+
+    /// Whether a response ended up at, or redirects to, `path`.
+    fn lands_on(&self, res: &transport::Response, path: &str) -> bool {
+        let url = self.url(path);
+        res.url == url
+            || res
+                .location()
+                .is_some_and(|location| location == path || location == url)
+    }
+
+    /// Wait for the rate limiter, if any.
+    fn throttle(&self) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire();
+        }
+    }
+
+    /// Fail with [Error::NotFound] or [Error::Deleted] if an item page holds
+    /// no item.
+    fn check_item(&self, id: &str, dom: &scraper::Html) -> Result<(), Error> {
+        match parse::detect_missing_item(dom) {
+            Some(parse::Missing::NotFound) => {
+                Err(Error::NotFound(format!("item {}", id)))
+            }
+            Some(parse::Missing::Deleted) => {
+                Err(Error::Deleted(id.to_string()))
+            }
+            // Stubs of hidden stories are left to the parser; Client::item
+            // reports them.
+            Some(parse::Missing::Dead)
+            | Some(parse::Missing::Flagged)
+            | None => Ok(()),
+        }
+    }
+
+    /// Parse a fetched document, reporting failures to the metrics.
+    fn parse_dom<T>(
+        &self,
+        dom: Fetched<scraper::Html>,
+        f: impl FnOnce(scraper::Html) -> Result<T, parse::ParseError>,
+    ) -> Result<Fetched<T>, Error> {
+        let endpoint = metrics::endpoint(&dom.source_url).to_string();
+        dom.try_map(f).map_err(|err| {
+            if let Some(metrics) = &self.metrics {
+                metrics.parse_failure(&endpoint);
+            }
+            err.into()
+        })
+    }
+
+    fn get_dom(
+        &self,
+        path: &str,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<scraper::Html>, Error> {
+        let html = self.get_html(path, opts)?;
+        if let Some(max) = self.limits.max_html_nodes {
+            // Checked on the raw HTML, so the tree is never built.
+            if count_start_tags(&html) > max {
+                return Err(Error::TooLarge {
+                    limit: max,
+                    unit: "nodes",
+                });
+            }
+        }
+        Ok(html.map(|html| scraper::Html::parse_document(&html)))
     }
 
     /// Get the current top posts.
-    pub fn top(&self, page: u64) -> Result<Vec<Post>, DynErr> {
-        let doc = self.get_dom(&format!("news?p={}", page))?;
-        parse::parse_list(doc).map_err(Into::into)
+    ///
+    /// Requesting a page past the end of the listing fails with
+    /// [Error::PageOutOfRange].
+    pub fn top(
+        &self,
+        list: impl Into<ListOptions>,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.top_with(list, &RequestOptions::default())
+    }
+
+    /// Like [Client::top], with per-call options.
+    pub fn top_with(
+        &self,
+        list: impl Into<ListOptions>,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.list("news", list.into(), opts)
+    }
+
+    /// Get the highest voted recent stories.
+    ///
+    /// Requesting a page past the end of the listing fails with
+    /// [Error::PageOutOfRange].
+    pub fn best(
+        &self,
+        list: impl Into<ListOptions>,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.best_with(list, &RequestOptions::default())
+    }
+
+    /// Like [Client::best], with per-call options.
+    pub fn best_with(
+        &self,
+        list: impl Into<ListOptions>,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.list("best", list.into(), opts)
+    }
+
+    /// Get the current Ask HN posts.
+    ///
+    /// These are self posts, so [Post::url] is `None`.
+    pub fn ask(
+        &self,
+        list: impl Into<ListOptions>,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.ask_with(list, &RequestOptions::default())
+    }
+
+    /// Like [Client::ask], with per-call options.
+    pub fn ask_with(
+        &self,
+        list: impl Into<ListOptions>,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.list("ask", list.into(), opts)
+    }
+
+    /// Get the current Show HN posts.
+    ///
+    /// Use [Post::title_without_prefix] to get the titles without the
+    /// `Show HN:` prefix.
+    pub fn show(
+        &self,
+        list: impl Into<ListOptions>,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.show_with(list, &RequestOptions::default())
+    }
+
+    /// Like [Client::show], with per-call options.
+    pub fn show_with(
+        &self,
+        list: impl Into<ListOptions>,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.list("show", list.into(), opts)
+    }
+
+    /// Get the current job postings.
+    ///
+    /// Jobs are returned as posts of kind [PostKind::Job], without score or
+    /// submitter.
+    pub fn jobs(
+        &self,
+        list: impl Into<ListOptions>,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.jobs_with(list, &RequestOptions::default())
+    }
+
+    /// Like [Client::jobs], with per-call options.
+    pub fn jobs_with(
+        &self,
+        list: impl Into<ListOptions>,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.list("jobs", list.into(), opts)
+    }
+
+    /// Get the most active discussions, in the order HN ranks them.
+    pub fn active(
+        &self,
+        list: impl Into<ListOptions>,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.active_with(list, &RequestOptions::default())
+    }
+
+    /// Like [Client::active], with per-call options.
+    pub fn active_with(
+        &self,
+        list: impl Into<ListOptions>,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.list("active", list.into(), opts)
+    }
+
+    /// Get the newest submissions.
+    ///
+    /// The listing moves quickly, so later pages are better reached by
+    /// following [Page::next] with [Client::follow] than by page number.
+    pub fn newest(
+        &self,
+        list: impl Into<ListOptions>,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.newest_with(list, &RequestOptions::default())
+    }
+
+    /// Like [Client::newest], with per-call options.
+    pub fn newest_with(
+        &self,
+        list: impl Into<ListOptions>,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.list("newest", list.into(), opts)
+    }
+
+    fn list(
+        &self,
+        path: &str,
+        list: ListOptions,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        let page = list.page;
+        let doc = self.get_dom(&format!("{}?p={}", path, page), opts)?;
+        let items = self.parse_dom(doc, parse::parse_list)?;
+        if items.items.is_empty() && page > 1 {
+            return Err(Error::PageOutOfRange { page });
+        }
+        Ok(items)
+    }
+
+    /// Check whether HN is reachable and serving this client.
+    ///
+    /// Fetches the small robots.txt once, without retries, eg. for
+    /// readiness probes.
+    pub fn health_check(&self) -> Health {
+        match self.probe("robots.txt") {
+            Ok(_) => Health::Ok,
+            Err(health) => health,
+        }
+    }
+
+    /// Fetch the page a [Cursor] points to.
+    ///
+    /// Works for both `?p=` and `?next=` style listings.
+    pub fn follow(
+        &self,
+        cursor: &Cursor,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        self.follow_with(cursor, &RequestOptions::default())
+    }
+
+    /// Like [Client::follow], with per-call options.
+    pub fn follow_with(
+        &self,
+        cursor: &Cursor,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<Page<Post>>, Error> {
+        let doc = self.get_dom(cursor.href(), opts)?;
+        self.parse_dom(doc, parse::parse_list)
+    }
+
+    /// Get a single item, reporting stubs as [Item::Unavailable].
+    ///
+    /// Dead and flagged stories are only shown to accounts that enabled
+    /// `showdead` in their profile, see [Capabilities::sees_dead]. For
+    /// other clients HN serves a stub, returned as
+    /// [UnavailableReason::Dead] or [UnavailableReason::Flagged]; fetch the
+    /// item with an [AuthenticatedClient] of such an account to get the
+    /// post instead. Nonexistent items fail with [Error::NotFound].
+    pub fn item(&self, id: &str) -> Result<Fetched<Item>, Error> {
+        let dom = self
+            .get_dom(&format!("item?id={}", id), &RequestOptions::default())?;
+        let reason = match parse::detect_missing_item(&dom) {
+            Some(parse::Missing::NotFound) => {
+                return Err(Error::NotFound(format!("item {}", id)))
+            }
+            Some(parse::Missing::Deleted) => Some(UnavailableReason::Deleted),
+            Some(parse::Missing::Dead) => Some(UnavailableReason::Dead),
+            Some(parse::Missing::Flagged) => Some(UnavailableReason::Flagged),
+            None => None,
+        };
+        match reason {
+            Some(reason) => Ok(dom.map(|_| Item::Unavailable { reason })),
+            None => Ok(self
+                .parse_dom(dom, |dom| self.parse_submission(id, dom))?
+                .map(Item::Post)),
+        }
     }
 
     /// Get a single post with comments.
-    pub fn submission(&self, id: &str) -> Result<Post, DynErr> {
+    pub fn submission(&self, id: &str) -> Result<Fetched<Post>, Error> {
+        self.submission_with(id, &RequestOptions::default())
+    }
+
+    /// Like [Client::submission], with per-call options.
+    pub fn submission_with(
+        &self,
+        id: &str,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<Post>, Error> {
         let url = format!("item?id={}", id);
-        let dom = self.get_dom(&url)?;
-        parse::parse_submission(id.to_string(), dom).map_err(Into::into)
+        let dom = self.get_dom(&url, opts)?;
+        self.check_item(id, &dom)?;
+        self.parse_dom(dom, |dom| self.parse_submission(id, dom))
+    }
+
+    fn parse_submission(
+        &self,
+        id: &str,
+        dom: scraper::Html,
+    ) -> Result<Post, parse::ParseError> {
+        let mut post = parse::parse_submission(id.to_string(), dom)?;
+        if let Some(transformer) = &self.transformer {
+            for comment in post.comments.iter_mut() {
+                transformer.apply(comment);
+            }
+        }
+        Ok(post)
+    }
+
+    /// Get a post with the comments from all pages of its thread.
+    ///
+    /// Large threads are split over multiple pages. `on_progress` is called
+    /// after each page with the number of comments fetched so far and the
+    /// total comment count reported by HN.
+    pub fn submission_all_pages(
+        &self,
+        id: &str,
+        mut on_progress: impl FnMut(usize, u64),
+    ) -> Result<Fetched<Post>, Error> {
+        let mut post = self.submission(id)?;
+        on_progress(post.comments.len(), post.comment_count);
+
+        while let Some(more) = post.value.truncated.take() {
+            let page = self.more_comments(id, &more)?.into_inner();
+            post.value.comments.extend(page.comments);
+            post.value.truncated = page.truncated;
+            on_progress(post.comments.len(), post.comment_count);
+        }
+
+        Ok(post)
+    }
+
+    /// Get a post with comments, with [Comment::author_profile] set.
+    ///
+    /// Profiles are fetched like [Client::users], so they are cached and
+    /// subject to the client's rate limits. Comments whose author's profile
+    /// could not be fetched have no profile.
+    pub fn submission_with_profiles(
+        &self,
+        id: &str,
+    ) -> Result<Fetched<Post>, Error> {
+        let mut post = self.submission(id)?;
+        let mut names = post
+            .comments
+            .iter()
+            .map(|comment| comment.username.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let profiles = self
+            .users(&names)
+            .succeeded
+            .into_iter()
+            .map(|(name, user)| (name, user.into_inner()))
+            .collect::<std::collections::HashMap<_, _>>();
+        for comment in post.value.comments.iter_mut() {
+            comment.author_profile = profiles.get(&comment.username).cloned();
+        }
+        Ok(post)
+    }
+
+    /// Fetch the next page of a truncated thread.
+    ///
+    /// The returned post only holds the comments of that page; its
+    /// `truncated` field points to the page after, if any.
+    pub fn more_comments(
+        &self,
+        id: &str,
+        more: &MoreComments,
+    ) -> Result<Fetched<Post>, Error> {
+        let dom = self.get_dom(&more.url, &RequestOptions::default())?;
+        let mut post =
+            self.parse_dom(dom, |dom| self.parse_submission(id, dom))?;
+        let fetched = post.comments.len() as u64;
+        if let Some(next) = &mut post.value.truncated {
+            next.remaining = more.remaining.saturating_sub(fetched);
+        }
+        Ok(post)
+    }
+
+    /// Get a comment with all its replies.
+    ///
+    /// Replies are nested in [Comment::children], with depths relative to
+    /// the requested comment.
+    pub fn comment_subtree(&self, id: &str) -> Result<Fetched<Comment>, Error> {
+        let dom = self
+            .get_dom(&format!("item?id={}", id), &RequestOptions::default())?;
+        self.check_item(id, &dom)?;
+        self.parse_dom(dom, |dom| {
+            let (mut comment, mut descendants) =
+                parse::parse_comment_page(&dom)?;
+            if let Some(transformer) = &self.transformer {
+                transformer.apply(&mut comment);
+                for reply in &mut descendants {
+                    transformer.apply(reply);
+                }
+            }
+            comment.children = Comment::nest(descendants);
+            Ok(comment)
+        })
+    }
+
+    /// Get a comment embedded in `levels` levels of surrounding thread, like
+    /// HN's "context" link.
+    ///
+    /// Returns the subtree of the ancestor `levels` levels up, or of the
+    /// top-level comment if the thread is not that deep. The requested
+    /// comment is among its descendants.
+    pub fn comment_with_context(
+        &self,
+        id: &str,
+        levels: usize,
+    ) -> Result<Fetched<Comment>, Error> {
+        let mut root = id.to_string();
+        for _ in 0..levels {
+            let dom = self.get_dom(
+                &format!("item?id={}", root),
+                &RequestOptions::default(),
+            )?;
+            match parse::parse_parent_comment_id(&dom) {
+                Some(parent) => root = parent,
+                None => break,
+            }
+        }
+        self.comment_subtree(&root)
     }
+
+    /// Get multiple posts with comments.
+    ///
+    /// Failures are collected in the report instead of aborting the batch.
+    pub fn submissions(
+        &self,
+        ids: &[&str],
+    ) -> BatchReport<String, Fetched<Post>> {
+        let mut report = BatchReport::new();
+        for id in ids {
+            let opts = RequestOptions::new().counting_retries();
+            let res = self.submission_with(id, &opts);
+            report.push(id.to_string(), res, opts.retries());
+        }
+        report
+    }
+
+    /// Get a user profile.
+    pub fn user(&self, username: &str) -> Result<Fetched<User>, Error> {
+        self.user_with(username, &RequestOptions::default())
+    }
+
+    /// Like [Client::user], with per-call options.
+    pub fn user_with(
+        &self,
+        username: &str,
+        opts: &RequestOptions,
+    ) -> Result<Fetched<User>, Error> {
+        let dom = self.get_dom(&format!("user?id={}", username), opts)?;
+        let user = self.parse_dom(dom, |dom| parse::parse_user(&dom))?;
+        self.profiles.insert(username, user.clone());
+        Ok(user)
+    }
+
+    /// Get multiple user profiles.
+    ///
+    /// Recently fetched profiles are served from the cache, see
+    /// [ClientBuilder::profile_cache_ttl]. The rest are fetched by a few
+    /// threads in parallel. Results are in input order; duplicate names
+    /// are reported once.
+    pub fn users(&self, names: &[&str]) -> BatchReport<String, Fetched<User>> {
+        self.users_with(names, &RequestOptions::default())
+    }
+
+    /// Like [Client::users], with per-call options.
+    pub fn users_with(
+        &self,
+        names: &[&str],
+        opts: &RequestOptions,
+    ) -> BatchReport<String, Fetched<User>> {
+        let mut results = std::collections::HashMap::new();
+        let mut missing = Vec::new();
+        for name in names {
+            let cached = if opts.bypass_cache {
+                None
+            } else {
+                self.profiles.get(name)
+            };
+            match cached {
+                Some(user) => {
+                    results.insert(name.to_string(), (Ok(user), 0));
+                }
+                None => missing.push(name.to_string()),
+            }
+        }
+        missing.sort();
+        missing.dedup();
+
+        let workers = missing.len().min(PROFILE_WORKERS);
+        let queue = Arc::new(std::sync::Mutex::new(missing));
+        let (tx, rx) = std::sync::mpsc::channel();
+        for _ in 0..workers {
+            let (client, queue, tx) = (self.clone(), queue.clone(), tx.clone());
+            let opts = opts.clone();
+            std::thread::spawn(move || loop {
+                let next = queue
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .pop();
+                match next {
+                    Some(name) => {
+                        let opts = opts.counting_retries();
+                        let res = client.user_with(&name, &opts);
+                        let _ = tx.send((name, (res, opts.retries())));
+                    }
+                    None => break,
+                }
+            });
+        }
+        drop(tx);
+        results.extend(rx);
+
+        let mut report = BatchReport::new();
+        for name in names {
+            if let Some((res, retries)) = results.remove(*name) {
+                report.push(name.to_string(), res, retries);
+            }
+        }
+        report
+    }
+}
+
+/// Number of start tags in `html`.
+///
+/// Tags in comments and scripts are counted too, so apart from the few
+/// elements the parser inserts, like `<tbody>`, this is an upper bound.
+fn count_start_tags(html: &str) -> usize {
+    html.as_bytes()
+        .windows(2)
+        .filter(|pair| pair[0] == b'<' && pair[1].is_ascii_alphabetic())
+        .count()
 }
 
+/// Helper threads per client for requests with a timeout, see [Deadlines].
+const MAX_DEADLINE_THREADS: usize = 16;
+
+/// Timeouts for transports that do not enforce them, see
+/// [transport::Transport::enforces_timeout].
+#[derive(Debug, Default)]
+struct Deadlines {
+    /// Helper threads still running.
+    running: std::sync::atomic::AtomicUsize,
+}
+
+/// Counts a helper thread as running until dropped, even if it panics.
+struct Running(Arc<Deadlines>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        use std::sync::atomic::Ordering;
+
+        self.0.running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Deadlines {
+    /// Run a blocking request on a separate thread, giving up after
+    /// `timeout`.
+    ///
+    /// The request itself can not be interrupted, so on timeout it is left
+    /// to finish in the background and its result is discarded. Once too
+    /// many of those pile up, further requests time out right away.
+    fn run<T, F>(
+        self: &Arc<Self>,
+        timeout: std::time::Duration,
+        f: F,
+    ) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, Error> + Send + 'static,
+    {
+        use std::sync::{atomic::Ordering, mpsc::RecvTimeoutError};
+
+        if self.running.fetch_add(1, Ordering::SeqCst) >= MAX_DEADLINE_THREADS {
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            return Err(Error::Timeout { after: timeout });
+        }
+        let running = Running(self.clone());
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let res = f();
+            drop(running);
+            // The receiver is gone if the deadline passed.
+            let _ = tx.send(res);
+        });
+        match rx.recv_timeout(timeout) {
+            Ok(res) => res,
+            Err(RecvTimeoutError::Timeout) => {
+                Err(Error::Timeout { after: timeout })
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(Error::Io(std::io::Error::other("Request thread panicked")))
+            }
+        }
+    }
+}
+
+/// Karma needed to downvote comments.
+const DOWNVOTE_KARMA: u64 = 501;
+/// Karma needed to flag submissions and comments.
+const FLAG_KARMA: u64 = 31;
+
+/// Hackernews client logged in to an account.
+///
+/// Like [Client] it is `Send + Sync` and cheap to clone; clones share the
+/// session and the [PostingGuards] state.
+#[derive(Clone)]
 pub struct AuthenticatedClient {
     client: Client,
+    username: String,
+    guard: Arc<guard::Guard>,
 }
 
 impl std::ops::Deref for AuthenticatedClient {
@@ -65,99 +1412,273 @@ impl std::ops::Deref for AuthenticatedClient {
 
 impl AuthenticatedClient {
     /// Log in.
-    pub fn login(username: &str, password: &str) -> Result<Self, DynErr> {
-        let inner = reqwest::Client::builder().cookie_store(true).build()?;
-
-        let _login_page = inner
-            .get("https://news.ycombinator.com/login?goto=news")
-            .send()?
-            .error_for_status()?
-            .text()?;
-
-        let res = inner
-            .post("https://news.ycombinator.com/login")
-            .form(&serde_json::json!({
-                "goto": "news",
-                "acct": username,
-                "pw": password,
-            }))
-            .send()?
-            .error_for_status()?;
-
-        if res.url().as_str() != "https://news.ycombinator.com/news" {
+    pub fn login(username: &str, password: &str) -> Result<Self, Error> {
+        Self::login_with(Client::builder().build()?, username, password)
+    }
+
+    /// Log in, using a preconfigured [Client].
+    pub fn login_with(
+        client: Client,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, Error> {
+        let correlation_id = transport::new_correlation_id();
+        let mut login_page =
+            transport::Request::get(client.url("login?goto=news"));
+        login_page.correlation_id = Some(correlation_id.clone());
+        client.send(login_page, None)?;
+
+        let res = client.retry.run(|| {
+            let mut request = transport::Request::post(
+                client.url("login"),
+                vec![("goto", "news"), ("acct", username), ("pw", password)],
+            );
+            request.correlation_id = Some(correlation_id.clone());
+            client.send(request, None)
+        })?;
+
+        if !client.lands_on(&res, "news") {
             // TODO: parse error message.
-            return Err(failure::format_err!(
-                "Login failued: invalid credentials?"
-            ));
+            return Err(Error::Auth("invalid credentials?".into()));
         }
 
         Ok(Self {
-            client: Client { inner },
+            client,
+            username: username.to_string(),
+            guard: Arc::new(guard::Guard::new(PostingGuards::default())),
         })
     }
 
     /// Create a new account.
-    pub fn signup(username: &str, password: &str) -> Result<Self, DynErr> {
-        let inner = reqwest::Client::builder().cookie_store(true).build()?;
-
-        let _login_page = inner
-            .get("https://news.ycombinator.com/login?goto=news")
-            .send()?
-            .error_for_status()?
-            .text()?;
-
-        let res = inner
-            .post("https://news.ycombinator.com/login")
-            .form(&serde_json::json!({
-                "goto": "news",
-                "creating": "t",
-                "acct": username,
-                "pw": password,
-            }))
-            .send()?
-            .error_for_status()?;
-
-        if res.url().as_str() != "https://news.ycombinator.com/news" {
+    pub fn signup(username: &str, password: &str) -> Result<Self, Error> {
+        Self::signup_with(Client::builder().build()?, username, password)
+    }
+
+    /// Create a new account, using a preconfigured [Client].
+    pub fn signup_with(
+        client: Client,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, Error> {
+        let correlation_id = transport::new_correlation_id();
+        let mut login_page =
+            transport::Request::get(client.url("login?goto=news"));
+        login_page.correlation_id = Some(correlation_id.clone());
+        client.send(login_page, None)?;
+
+        let res = client.retry.run(|| {
+            let mut request = transport::Request::post(
+                client.url("login"),
+                vec![
+                    ("goto", "news"),
+                    ("creating", "t"),
+                    ("acct", username),
+                    ("pw", password),
+                ],
+            );
+            request.correlation_id = Some(correlation_id.clone());
+            client.send(request, None)
+        })?;
+
+        if !client.lands_on(&res, "news") {
             // TODO: parse error message.
-            return Err(failure::format_err!("Signup failed"));
+            return Err(Error::Auth("Signup failed".into()));
         }
 
         Ok(Self {
-            client: Client { inner },
+            client,
+            username: username.to_string(),
+            guard: Arc::new(guard::Guard::new(PostingGuards::default())),
+        })
+    }
+
+    /// Replace the default [PostingGuards].
+    pub fn posting_guards(mut self, guards: PostingGuards) -> Self {
+        self.guard = Arc::new(guard::Guard::new(guards));
+        self
+    }
+
+    /// Name of the logged in account.
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// Determine what the account is allowed to do.
+    ///
+    /// Karma thresholds are the commonly cited HN values; poll creation and
+    /// the `showdead` setting are read from the respective pages.
+    pub fn capabilities(&self) -> Result<Capabilities, Error> {
+        let opts = RequestOptions::new().user_action();
+        let profile =
+            self.get_dom(&format!("user?id={}", self.username), &opts)?;
+        let user = parse::parse_user(&profile)?;
+        let sees_dead = parse::parse_showdead(&profile).unwrap_or(false);
+
+        let newpoll = self.get_dom("newpoll", &opts)?;
+        let can_create_polls = parse::has_fnid_form(&newpoll);
+
+        Ok(Capabilities {
+            karma: user.karma,
+            can_downvote: user.karma >= DOWNVOTE_KARMA,
+            can_flag: user.karma >= FLAG_KARMA,
+            can_create_polls,
+            sees_dead,
         })
     }
 
+    /// Like [Client::health_check], but also checks that the session is
+    /// still logged in.
+    pub fn health_check(&self) -> Health {
+        match self.probe(&format!("user?id={}", self.username)) {
+            Ok(page) if page.contains("logout?auth=") => Health::Ok,
+            Ok(_) => Health::LoggedOut,
+            Err(health) => health,
+        }
+    }
+
     /// Up or downvote a post or comment.
     ///
     /// a [VoteAction] can be retrieved from the [Post] and [Post] types.
-    pub fn vote(&self, action: &VoteAction) -> Result<(), reqwest::Error> {
-        let url = format!("https://news.ycombinator.com/{}", action.url());
-        self.client.inner.get(&url).send()?.error_for_status()?;
+    pub fn vote(&self, action: &VoteAction) -> Result<(), Error> {
+        let reservation = self.guard.reserve_vote(action)?;
+        let url = self.url(action.url());
+        self.throttle();
+        self.send(transport::Request::get(url), None)?;
+        reservation.keep();
         Ok(())
     }
+
+    /// Perform multiple votes.
+    ///
+    /// Failures are collected in the report instead of aborting the batch.
+    pub fn vote_many(
+        &self,
+        actions: &[VoteAction],
+    ) -> BatchReport<VoteAction, ()> {
+        let mut report = BatchReport::new();
+        for action in actions {
+            // Votes are sent once, never retried.
+            report.push(action.clone(), self.vote(action), 0);
+        }
+        report
+    }
+
+    /// Create a poll and return its id.
+    ///
+    /// Requires an account with enough karma for poll creation, see
+    /// [AuthenticatedClient::capabilities].
+    pub fn create_poll(
+        &self,
+        title: &str,
+        text: &str,
+        options: &[&str],
+    ) -> Result<String, Error> {
+        let mut fields = vec![title, text];
+        fields.extend_from_slice(options);
+        let reservation = self.guard.reserve_submission(&fields)?;
+
+        let correlation_id = transport::new_correlation_id();
+        let opts = RequestOptions::new()
+            .correlation_id(correlation_id.as_str())
+            .user_action();
+        let page = self.get_dom("newpoll", &opts)?;
+        let form = parse::parse_fnid_form(&page).map_err(|_| {
+            Error::Auth("Poll creation not available for this account".into())
+        })?;
+
+        // The form asks for the title, the text and the choices, with
+        // choices separated by blank lines.
+        let values =
+            [title.to_string(), text.to_string(), options.join("\n\n")];
+        if form.fields.len() < values.len() {
+            return Err(
+                parse::ParseError::new("Unexpected poll form layout").into()
+            );
+        }
+        let mut data = form.hidden.clone();
+        data.extend(form.fields.iter().cloned().zip(values.iter().cloned()));
+
+        // HN redirects to the newest page, so the poll is looked up among
+        // the account's submissions. Remember the ones from before, so an
+        // older poll with the same title is not taken for the new one.
+        let submitted = format!("submitted?id={}", self.username);
+        let existing =
+            parse::parse_list(self.get_dom(&submitted, &opts)?.into_inner())?
+                .items
+                .into_iter()
+                .map(|post| post.id)
+                .collect::<std::collections::HashSet<_>>();
+
+        let url = self.url(&form.action);
+        let mut request = transport::Request::post(url, data);
+        request.correlation_id = Some(correlation_id);
+        let res = self.send(request, None)?;
+        // Rejected polls get the form again, with an error message.
+        let redirected = !res.redirects.is_empty() || res.location().is_some();
+        if !redirected || self.lands_on(&res, "newpoll") {
+            return Err(Error::Auth("HN did not accept the poll".into()));
+        }
+        reservation.keep();
+
+        let id =
+            parse::parse_list(self.get_dom(&submitted, &opts)?.into_inner())?
+                .items
+                .into_iter()
+                .find(|post| {
+                    post.title == title && !existing.contains(&post.id)
+                })
+                .map(|post| post.id)
+                .ok_or_else(|| {
+                    parse::ParseError::new("Could not find created poll")
+                })?;
+        Ok(id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{
+        list_fixture, login_routes, mock_client, mock_login, thread_fixture,
+    };
 
     #[test]
     fn test_top() {
-        let c = Client::new();
-        let items = c.top(1).unwrap();
-        assert!(items.len() >= 20);
+        let c = Client::try_new().unwrap();
+        let page = c.top(1).unwrap();
+        assert!(page.items.len() >= 20);
+
+        let next = c.follow(page.next.as_ref().unwrap()).unwrap();
+        assert!(next.items.len() >= 20);
+    }
+
+    #[test]
+    fn test_top_out_of_range() {
+        let c = Client::try_new().unwrap();
+        match c.top(500) {
+            Err(Error::PageOutOfRange { page: 500 }) => {}
+            other => panic!("Expected PageOutOfRange, got {:?}", other),
+        }
     }
 
     #[test]
     fn test_submission() {
-        let c = Client::new();
+        let c = Client::try_new().unwrap();
         let s = c.submission("20993456").unwrap();
 
         assert_eq!(
             s.title,
             "Where you are born is more predictive of your future than any other factor"
         );
-        assert!(s.score > 150);
+        assert!(s.score.unwrap() > 150);
+    }
+
+    #[test]
+    fn test_user() {
+        let c = Client::try_new().unwrap();
+        let user = c.user("pg").unwrap();
+        assert_eq!(user.username, "pg");
+        assert!(user.karma > 100_000);
     }
 
     #[test]
@@ -171,6 +1692,7 @@ mod tests {
         let items = c.top(0).unwrap();
 
         let item = items
+            .items
             .iter()
             .find(|item| {
                 item.vote.as_ref().map(|v| v.is_upvote()).unwrap_or(false)
@@ -182,12 +1704,505 @@ mod tests {
 
         let sub = c.submission(&item.id).unwrap();
         let down = sub.vote.as_ref().unwrap();
-        assert_eq!(down.is_upvote(), false);
+        assert!(!down.is_upvote());
 
         c.vote(down).unwrap();
 
         let sub = c.submission(&item.id).unwrap();
         let up = sub.vote.as_ref().unwrap();
-        assert_eq!(up.is_upvote(), true);
+        assert!(up.is_upvote());
+    }
+
+    #[test]
+    fn test_comment_with_context() {
+        use transport::MockTransport;
+
+        let comment_page = |id: u64, parent: u64| {
+            format!(
+                "<html><body><table class='fatitem'>\
+                 <tr class='athing' id='{id}'><td class='default'>\
+                 <span class='comhead'><a class='hnuser'>u{id}</a> \
+                 <span class='age'><a>1 hour ago</a></span> | \
+                 <a href='item?id={parent}'>parent</a> | \
+                 <span class='onstory'>on: <a href='item?id=1'>Story</a>\
+                 </span></span><div class='comment'>Comment {id}</div>\
+                 </td></tr></table><table class='comment-tree'></table>\
+                 </body></html>",
+                id = id,
+                parent = parent,
+            )
+        };
+        let client = mock_client(
+            MockTransport::new()
+                .page("item?id=4", &comment_page(4, 3))
+                .page("item?id=3", &comment_page(3, 2))
+                .page("item?id=2", &comment_page(2, 1)),
+        );
+
+        assert_eq!(client.comment_with_context("4", 1).unwrap().id, "3");
+        // The top-level comment is as far up as it goes.
+        assert_eq!(client.comment_with_context("4", 5).unwrap().id, "2");
+    }
+
+    #[test]
+    fn test_item_unavailable() {
+        let stub = "<html><body><table class='fatitem'>\
+                    <tr class='athing' id='3'><td class='title'>[dead]</td>\
+                    </tr></table></body></html>";
+        let mock = transport::MockTransport::new()
+            .page("item?id=1", &thread_fixture(&[(2, 0)]))
+            .page("item?id=3", stub)
+            .page("item?id=4", "No such item.");
+        let client = mock_client(mock);
+        assert!(matches!(client.item("1").unwrap().value, Item::Post(_)));
+        assert_eq!(
+            client.item("3").unwrap().value,
+            Item::Unavailable {
+                reason: UnavailableReason::Dead
+            }
+        );
+        assert!(matches!(client.item("4"), Err(Error::NotFound(_))));
+    }
+
+    #[test]
+    fn test_max_html_nodes() {
+        assert_eq!(count_start_tags("<p>a < b</p><br/><!-- x -->"), 2);
+
+        let client = |max| {
+            Client::builder()
+                .base_url("http://hn.test")
+                .transport(Arc::new(
+                    transport::MockTransport::new()
+                        .page("news?p=1", &list_fixture(&[1, 2, 3])),
+                ))
+                .max_html_nodes(max)
+                .build()
+                .unwrap()
+        };
+        assert!(matches!(
+            client(10).top(1),
+            Err(Error::TooLarge { limit: 10, .. })
+        ));
+        assert_eq!(client(1000).top(1).unwrap().items.len(), 3);
+    }
+
+    #[test]
+    fn test_deadline_threads() {
+        use std::time::Duration;
+
+        let deadlines = Arc::new(Deadlines::default());
+        let timeout = Duration::from_millis(1);
+        for _ in 0..MAX_DEADLINE_THREADS {
+            let res = deadlines.run(timeout, || {
+                std::thread::sleep(Duration::from_millis(200));
+                Ok(())
+            });
+            assert!(matches!(res, Err(Error::Timeout { .. })));
+        }
+        // Too many abandoned requests are still running.
+        assert!(matches!(
+            deadlines.run(Duration::from_secs(1), || Ok(())),
+            Err(Error::Timeout { .. })
+        ));
+        std::thread::sleep(Duration::from_millis(400));
+        assert!(deadlines.run(Duration::from_secs(1), || Ok(())).is_ok());
+    }
+
+    #[test]
+    fn test_clients_are_shareable() {
+        fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+        assert_shareable::<Client>();
+        assert_shareable::<AuthenticatedClient>();
+    }
+
+    #[test]
+    fn test_mock_login_and_vote() {
+        use transport::{Method, MockTransport};
+
+        let mock = Arc::new(
+            MockTransport::new()
+                .page("login?goto=news", "<html></html>")
+                .redirect(Method::Post, "login", "news", "<html></html>")
+                .page("vote?id=1&how=up", "<html></html>"),
+        );
+        let responses = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = responses.clone();
+        let client = Client::builder()
+            .base_url("http://hn.test")
+            .transport(mock.clone())
+            .on_request(|request| {
+                request.headers.push(("x-test".into(), "1".into()))
+            })
+            .on_response(move |_, res| {
+                assert!(res.is_ok());
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            })
+            .build()
+            .unwrap();
+        let auth = AuthenticatedClient::login_with(client, "u", "p").unwrap();
+        auth.vote(&VoteAction::Upvote("vote?id=1&how=up".into()))
+            .unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|r| r.headers == vec![("x-test".into(), "1".into())]));
+        assert!(requests[1].form.contains(&("acct".into(), "u".into())));
+        assert_eq!(requests[2].url, "http://hn.test/vote?id=1&how=up");
+        // Both login requests belong to one operation, the vote to another.
+        assert!(requests[0].correlation_id.is_some());
+        assert_eq!(requests[0].correlation_id, requests[1].correlation_id);
+        assert_ne!(requests[1].correlation_id, requests[2].correlation_id);
+        assert_eq!(responses.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+        let client = mock_client(MockTransport::new());
+        assert!(matches!(
+            client.user("pg"),
+            Err(Error::Status { status: 404, .. })
+        ));
+    }
+
+    #[test]
+    fn test_unavailable() {
+        use transport::{Method, MockTransport};
+
+        let mock = MockTransport::new()
+            .page(
+                "news?p=1",
+                "<html><body>Sorry for the inconvenience. HN is down for \
+                 maintenance.</body></html>",
+            )
+            .respond(Method::Get, "user?id=pg", 522, "");
+        let client = mock_client(mock);
+        let err = client.top(1).unwrap_err();
+        assert!(matches!(err, Error::Unavailable { retry_hint: None }));
+        assert!(err.is_retryable());
+        assert!(matches!(client.user("pg"), Err(Error::Unavailable { .. })));
+    }
+
+    #[test]
+    fn test_listings() {
+        let mock = transport::MockTransport::new()
+            .page("newest?p=1", &list_fixture(&[3, 2, 1]))
+            .page("best?p=1", &list_fixture(&[7]))
+            .page("active?p=1", &list_fixture(&[8, 6]))
+            .page(
+                "ask?p=1",
+                &list_fixture(&[9])
+                    .replace("https://example.com/9", "item?id=9")
+                    .replace("Story 9", "Ask HN: Story 9"),
+            )
+            .page(
+                "jobs?p=1",
+                "<html><body><table class='itemlist'>\
+                 <tr class='athing' id='4'><td class='title'>\
+                 <a href='https://jobs.example.com/'>Example is hiring</a>\
+                 </td></tr><tr><td class='subtext'>\
+                 <span class='age'><a href='item?id=4'>1 hour ago</a></span>\
+                 </td></tr></table></body></html>",
+            )
+            .page(
+                "show?p=1",
+                &list_fixture(&[5]).replace("Story 5", "Show HN: Story 5"),
+            );
+        let client = mock_client(mock);
+        let page = client.newest(1).unwrap();
+        let ids = page.items.iter().map(|p| p.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["3", "2", "1"]);
+        let best = client.best(1).unwrap();
+        assert_eq!(best.items[0].id, "7");
+        assert_eq!(best.items[0].url.as_deref(), Some("https://example.com/7"));
+        let ask = client.ask(1).unwrap();
+        assert_eq!(ask.items[0].title, "Ask HN: Story 9");
+        assert_eq!(ask.items[0].url, None);
+        assert_eq!(ask.items[0].title_without_prefix(), "Story 9");
+        assert_eq!(ask.items[0].kind, PostKind::Story);
+        let active = client.active(1).unwrap();
+        let ids = active
+            .items
+            .iter()
+            .map(|p| p.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["8", "6"]);
+        let jobs = client.jobs(1).unwrap();
+        assert_eq!(jobs.items[0].kind, PostKind::Job);
+        assert_eq!(jobs.items[0].score, None);
+        assert_eq!(jobs.items[0].username, None);
+        let show = client.show(1).unwrap();
+        assert_eq!(show.items[0].title_without_prefix(), "Story 5");
+        assert_eq!(best.items[0].title_without_prefix(), "Story 7");
+    }
+
+    #[test]
+    fn test_health_check() {
+        use transport::{Method, MockTransport};
+
+        let ok = mock_client(MockTransport::new().page("robots.txt", ""));
+        assert_eq!(ok.health_check(), Health::Ok);
+        let banned =
+            MockTransport::new().respond(Method::Get, "robots.txt", 403, "");
+        assert_eq!(mock_client(banned).health_check(), Health::Blocked);
+        let down =
+            MockTransport::new().respond(Method::Get, "robots.txt", 502, "");
+        assert_eq!(mock_client(down).health_check(), Health::Down);
+
+        let auth = mock_login(
+            MockTransport::new()
+                .page("user?id=u", "<a href='login?goto=user'>login</a>"),
+        );
+        assert_eq!(auth.health_check(), Health::LoggedOut);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let mock = transport::MockTransport::new()
+            .page(
+                "user?id=u",
+                "<html><body><table>\
+                 <tr><td>user:</td><td><a class='hnuser'>u</a></td></tr>\
+                 <tr><td>created:</td><td>1 day ago</td></tr>\
+                 <tr><td>karma:</td><td>42</td></tr>\
+                 <tr><td>showdead:</td><td><select name='showd'>\
+                 <option>no</option><option selected>yes</option>\
+                 </select></td></tr></table></body></html>",
+            )
+            .page("newpoll", "<html><body>Not enough karma.</body></html>");
+        // Robots.txt does not apply to explicit user actions.
+        let client = Client::builder()
+            .base_url("http://hn.test")
+            .transport(Arc::new(login_routes(mock)))
+            .robots_policy(RobotsPolicy::parse(
+                "User-Agent: *\nDisallow: /user?\nDisallow: /newpoll\n",
+            ))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.get_html("newpoll", &RequestOptions::default()),
+            Err(Error::RobotsDisallowed { .. })
+        ));
+        let auth = AuthenticatedClient::login_with(client, "u", "p").unwrap();
+        assert_eq!(
+            auth.capabilities().unwrap(),
+            Capabilities {
+                karma: 42,
+                can_downvote: false,
+                can_flag: true,
+                can_create_polls: false,
+                sees_dead: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_create_poll() {
+        use transport::{Method, MockTransport};
+
+        let form = "<html><body><form action='r' method='post'>\
+                    <input type='hidden' name='fnid' value='f1'>\
+                    <input name='title'><textarea name='text'></textarea>\
+                    <textarea name='choices'></textarea>\
+                    <input type='submit' value='submit'></form></body></html>";
+        let polls = |ids: &[u64]| {
+            ids.iter().fold(list_fixture(ids), |html, id| {
+                html.replace(&format!("Story {}", id), "Best editor?")
+            })
+        };
+        // Robots.txt does not apply to explicit user actions.
+        let robots = RobotsPolicy::parse(
+            "User-Agent: *\nDisallow: /submitted?\nDisallow: /newpoll\n",
+        );
+        let auth = |mock: MockTransport| {
+            let client = Client::builder()
+                .base_url("http://hn.test")
+                .transport(Arc::new(login_routes(mock.page("newpoll", form))))
+                .robots_policy(robots.clone())
+                .build()
+                .unwrap();
+            AuthenticatedClient::login_with(client, "u", "p").unwrap()
+        };
+        let choices = ["vi", "emacs"];
+
+        // An older poll with the same title is not taken for the new one.
+        let mock = MockTransport::new()
+            .page("submitted?id=u", &polls(&[5]))
+            .page("submitted?id=u", &polls(&[6, 5]))
+            .redirect(Method::Post, "r", "newest", "<html></html>");
+        let created = auth(mock);
+        assert_eq!(
+            created.create_poll("Best editor?", "", &choices).unwrap(),
+            "6"
+        );
+        assert!(matches!(
+            created.create_poll("Best editor?", "", &choices),
+            Err(Error::Refused(_))
+        ));
+
+        let mock = MockTransport::new()
+            .page("submitted?id=u", &polls(&[5]))
+            .redirect(Method::Post, "r", "newest", "<html></html>");
+        assert!(matches!(
+            auth(mock).create_poll("Best editor?", "", &choices),
+            Err(Error::Parse(_))
+        ));
+
+        // A rejected poll can be retried, the guard only counts accepted
+        // ones.
+        let mock = MockTransport::new()
+            .page("submitted?id=u", &polls(&[5]))
+            .respond(Method::Post, "r", 200, form);
+        let rejected = auth(mock);
+        for _ in 0..2 {
+            assert!(matches!(
+                rejected.create_poll("Best editor?", "", &choices),
+                Err(Error::Auth(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_forbidden() {
+        use transport::{Method, MockTransport};
+
+        let mock = MockTransport::new()
+            .redirect(
+                Method::Get,
+                "upvoted?id=bob",
+                "login?goto=upvoted%3Fid%3Dbob",
+                "<html></html>",
+            )
+            .page("favorites?id=bob&comments=t", "Can't display that.");
+        let client = mock_client(mock);
+        let opts = RequestOptions::default();
+        assert!(matches!(
+            client.get_html("upvoted?id=bob", &opts),
+            Err(Error::Forbidden(path)) if path == "upvoted?id=bob"
+        ));
+        assert!(matches!(
+            client.get_html("favorites?id=bob&comments=t", &opts),
+            Err(Error::Forbidden(_))
+        ));
+
+        // Like a reqwest client following redirects without recording them.
+        struct Followed;
+        impl transport::Transport for Followed {
+            fn send(
+                &self,
+                _: &transport::Request,
+            ) -> Result<transport::Response, Error> {
+                Ok(transport::Response {
+                    status: 200,
+                    url: "http://hn.test/login?goto=upvoted".into(),
+                    redirects: Vec::new(),
+                    headers: Vec::new(),
+                    body: "<html></html>".into(),
+                })
+            }
+        }
+        let client = mock_client(Followed);
+        assert!(matches!(
+            client.get_html("upvoted?id=bob", &opts),
+            Err(Error::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn test_redirect_trace() {
+        use transport::{Method, MockTransport};
+
+        let mock = MockTransport::new().redirect(
+            Method::Get,
+            "item?id=1",
+            "item?id=2",
+            "<html></html>",
+        );
+        let client = mock_client(mock);
+        let page = client
+            .get_html("item?id=1", &RequestOptions::default())
+            .unwrap();
+        assert_eq!(page.source_url, "http://hn.test/item?id=2");
+        assert_eq!(page.redirects, vec!["http://hn.test/item?id=1"]);
+        assert_eq!(page.status, 200);
+        assert_eq!(page.size, "<html></html>".len());
+    }
+
+    #[test]
+    fn test_users_uses_cache() {
+        let profile = |name: &str| {
+            format!(
+                "<html><body><table><tr><td>user:</td><td>{}</td></tr>\
+                 <tr><td>created:</td><td>2010</td></tr>\
+                 <tr><td>karma:</td><td>5</td></tr></table></body></html>",
+                name
+            )
+        };
+        let mock = Arc::new(
+            transport::MockTransport::new()
+                .page("user?id=a", &profile("a"))
+                .page("user?id=b", &profile("b")),
+        );
+        let client = Client::builder().transport(mock.clone()).build().unwrap();
+
+        client.user("a").unwrap();
+        let report = client.users(&["b", "a", "c"]);
+        let names = report
+            .succeeded
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["b", "a"]);
+        assert_eq!(report.failed.len(), 1);
+        // "a" came from the cache.
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_submissions_counts_retried() {
+        let mock = transport::MockTransport::new()
+            .respond(transport::Method::Get, "item?id=1", 503, "")
+            .page("item?id=1", &thread_fixture(&[]))
+            .page("item?id=2", &thread_fixture(&[]));
+        let client = Client::builder()
+            .base_url("http://hn.test")
+            .transport(Arc::new(mock))
+            .retry_policy(
+                RetryPolicy::new()
+                    .base_delay(std::time::Duration::from_millis(1))
+                    .jitter(false),
+            )
+            .build()
+            .unwrap();
+
+        let report = client.submissions(&["1", "2"]);
+        assert_eq!(report.succeeded.len(), 2);
+        assert_eq!(report.retried, 1);
+    }
+
+    #[test]
+    fn test_base_url() {
+        let client = Client::builder()
+            .base_url("http://localhost:8080")
+            .build()
+            .unwrap();
+        assert_eq!(client.url("login"), "http://localhost:8080/login");
+
+        let post = Post::new("1", "Title");
+        let comment = Comment::new("2", "pg", "Text");
+        assert_eq!(
+            client.discussion_url(&post),
+            "http://localhost:8080/item?id=1"
+        );
+        assert_eq!(
+            client.permalink(&comment),
+            "http://localhost:8080/item?id=2"
+        );
+        assert_eq!(
+            post.comment_url(&comment),
+            "https://news.ycombinator.com/item?id=1#2"
+        );
+        assert_eq!(
+            comment.permalink(),
+            "https://news.ycombinator.com/item?id=2"
+        );
     }
 }
@@ -0,0 +1,126 @@
+/// Rough classification of a linked resource, eg. for preview badges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MediaKind {
+    Image,
+    Video,
+    Pdf,
+    CodeRepository,
+    Social,
+    Other,
+}
+
+const IMAGE_EXTENSIONS: &[&str] =
+    &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp"];
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "webm", "mov", "mkv", "avi"];
+
+const IMAGE_HOSTS: &[&str] = &["i.imgur.com", "i.redd.it"];
+const VIDEO_HOSTS: &[&str] =
+    &["youtube.com", "m.youtube.com", "youtu.be", "vimeo.com"];
+const CODE_HOSTS: &[&str] = &[
+    "github.com",
+    "gitlab.com",
+    "bitbucket.org",
+    "codeberg.org",
+    "git.sr.ht",
+    "sr.ht",
+];
+const SOCIAL_HOSTS: &[&str] = &[
+    "twitter.com",
+    "mobile.twitter.com",
+    "facebook.com",
+    "linkedin.com",
+    "reddit.com",
+    "old.reddit.com",
+    "instagram.com",
+    "mastodon.social",
+];
+
+/// Split a URL into its (lowercased) host and path.
+///
+/// The path keeps its case and excludes query and fragment.
+pub(crate) fn host_and_path(url: &str) -> (String, String) {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let rest = rest.split(['?', '#']).next().unwrap_or("");
+    let mut parts = rest.splitn(2, '/');
+    let host = parts
+        .next()
+        .unwrap_or("")
+        .rsplit('@')
+        .next()
+        .unwrap_or("")
+        .split(':')
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    let host = host.trim_start_matches("www.").to_string();
+    let path = parts.next().unwrap_or("").to_string();
+    (host, path)
+}
+
+/// Classify a URL by its host and file extension.
+pub fn classify_url(url: &str) -> MediaKind {
+    let (host, path) = host_and_path(url);
+
+    let file = path.rsplit('/').next().unwrap_or("");
+    let extension = match file.rfind('.') {
+        Some(index) => file[index + 1..].to_lowercase(),
+        None => String::new(),
+    };
+    let extension = extension.as_str();
+
+    if extension == "pdf" {
+        MediaKind::Pdf
+    } else if IMAGE_EXTENSIONS.contains(&extension)
+        || IMAGE_HOSTS.contains(&host.as_str())
+    {
+        MediaKind::Image
+    } else if VIDEO_EXTENSIONS.contains(&extension)
+        || VIDEO_HOSTS.contains(&host.as_str())
+    {
+        MediaKind::Video
+    } else if CODE_HOSTS.contains(&host.as_str()) {
+        MediaKind::CodeRepository
+    } else if SOCIAL_HOSTS.contains(&host.as_str()) {
+        MediaKind::Social
+    } else {
+        MediaKind::Other
+    }
+}
+
+/// Classify a post title by the `[pdf]` / `[video]` markers HN appends.
+pub(crate) fn classify_title(title: &str) -> Option<MediaKind> {
+    let title = title.trim_end().to_lowercase();
+    if title.ends_with("[pdf]") {
+        Some(MediaKind::Pdf)
+    } else if title.ends_with("[video]") {
+        Some(MediaKind::Video)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_url() {
+        assert_eq!(
+            classify_url("https://github.com/rust-lang/rust"),
+            MediaKind::CodeRepository
+        );
+        assert_eq!(
+            classify_url("https://www.youtube.com/watch?v=abc"),
+            MediaKind::Video
+        );
+        assert_eq!(
+            classify_url("http://example.com/paper.PDF?dl=1"),
+            MediaKind::Pdf
+        );
+        assert_eq!(
+            classify_url("https://example.com/img/cat.jpeg"),
+            MediaKind::Image
+        );
+        assert_eq!(classify_url("https://example.com/blog"), MediaKind::Other);
+    }
+}
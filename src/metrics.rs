@@ -0,0 +1,85 @@
+use std::{sync::Arc, time::Duration};
+
+/// Receives measurements of the client's requests, eg. to feed Prometheus
+/// counters.
+///
+/// `endpoint` is the requested path without query, like `item` or `news`.
+/// All methods default to doing nothing.
+pub trait Metrics: Send + Sync {
+    /// A request completed. `status` is `None` if no response was received.
+    fn request(&self, endpoint: &str, status: Option<u16>, latency: Duration) {
+        let _ = (endpoint, status, latency);
+    }
+
+    /// A failed request is retried; `attempt` is the upcoming attempt,
+    /// starting at 2.
+    fn retry(&self, endpoint: &str, attempt: u32) {
+        let _ = (endpoint, attempt);
+    }
+
+    /// A page was fetched but could not be parsed.
+    fn parse_failure(&self, endpoint: &str) {
+        let _ = endpoint;
+    }
+}
+
+/// A shared metrics sink, debug-printable for [crate::ClientBuilder].
+#[derive(Clone)]
+pub(crate) struct Shared(pub Arc<dyn Metrics>);
+
+impl std::fmt::Debug for Shared {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("Metrics")
+    }
+}
+
+/// Endpoint name of an absolute URL.
+pub(crate) fn endpoint(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let path = rest.find('/').map_or("", |index| &rest[index + 1..]);
+    path.split('?').next().unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{transport::MockTransport, Client};
+
+    #[test]
+    fn test_metrics() {
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl Metrics for Recorder {
+            fn request(
+                &self,
+                endpoint: &str,
+                status: Option<u16>,
+                _latency: Duration,
+            ) {
+                let event = format!("{} {:?}", endpoint, status);
+                self.0.lock().unwrap().push(event);
+            }
+
+            fn parse_failure(&self, endpoint: &str) {
+                self.0.lock().unwrap().push(format!("{} parse", endpoint));
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let client = Client::builder()
+            .transport(Arc::new(
+                MockTransport::new().page("user?id=pg", "<html/>"),
+            ))
+            .metrics(recorder.clone())
+            .build()
+            .unwrap();
+        assert!(client.user("pg").is_err());
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec!["user Some(200)".to_string(), "user parse".to_string()]
+        );
+    }
+}
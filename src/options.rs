@@ -0,0 +1,124 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use super::retry::RetryPolicy;
+
+/// Options for listing calls like [crate::Client::top].
+///
+/// A plain page number converts into options for that page.
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct ListOptions {
+    /// Page number, starting at 1.
+    pub page: u64,
+}
+
+impl ListOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn page(mut self, page: u64) -> Self {
+        self.page = page;
+        self
+    }
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        Self { page: 1 }
+    }
+}
+
+impl From<u64> for ListOptions {
+    fn from(page: u64) -> Self {
+        Self::new().page(page)
+    }
+}
+
+/// Per-call overrides of the client defaults.
+#[non_exhaustive]
+#[derive(Clone, Debug, Default)]
+pub struct RequestOptions {
+    /// Give up waiting for the response after this long.
+    pub timeout: Option<Duration>,
+    /// Retry policy replacing the client's.
+    pub retry: Option<RetryPolicy>,
+    /// Skip cached data and always fetch.
+    pub bypass_cache: bool,
+    /// Headers added to the request.
+    pub headers: Vec<(String, String)>,
+    /// Id passed to the hooks with each request, see
+    /// [crate::transport::Request::correlation_id]. Generated if unset.
+    pub correlation_id: Option<String>,
+    /// Fetched for an explicit user action rather than crawling, so
+    /// robots.txt does not apply.
+    pub(crate) user_action: bool,
+    /// Counts the retries made for this call.
+    pub(crate) retries: Option<Arc<AtomicUsize>>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    pub fn bypass_cache(mut self, bypass: bool) -> Self {
+        self.bypass_cache = bypass;
+        self
+    }
+
+    pub fn header(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.correlation_id = Some(id.into());
+        self
+    }
+
+    pub(crate) fn user_action(mut self) -> Self {
+        self.user_action = true;
+        self
+    }
+
+    /// These options with a fresh retry counter, for one input of a batch.
+    pub(crate) fn counting_retries(&self) -> Self {
+        let mut opts = self.clone();
+        opts.retries = Some(Arc::new(AtomicUsize::new(0)));
+        opts
+    }
+
+    pub(crate) fn retried(&self) {
+        if let Some(retries) = &self.retries {
+            retries.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Retries counted since [RequestOptions::counting_retries].
+    pub(crate) fn retries(&self) -> usize {
+        self.retries
+            .as_ref()
+            .map_or(0, |retries| retries.load(Ordering::Relaxed))
+    }
+}
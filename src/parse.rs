@@ -1,6 +1,9 @@
 use scraper::{ElementRef, Html as Document, Selector};
 
-use super::types::{Comment, Post, VoteAction};
+use super::types::{
+    Comment, Cursor, MoreComments, Ordered, Page, Post, PostActions, PostKind,
+    PostMeta, User, VoteAction, VoteAvailability,
+};
 
 #[derive(Debug)]
 pub struct ParseError {
@@ -8,7 +11,7 @@ pub struct ParseError {
 }
 
 impl ParseError {
-    fn new(msg: impl Into<String>) -> Self {
+    pub(crate) fn new(msg: impl Into<String>) -> Self {
         Self {
             message: msg.into(),
         }
@@ -23,8 +26,21 @@ impl std::fmt::Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
-fn sel(s: &str) -> Selector {
-    Selector::parse(s).unwrap()
+/// Markup generation of a page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// The markup news.ycombinator.com serves today.
+    Current,
+    /// Older markup found in historical snapshots, eg. on the Wayback
+    /// Machine: no `athing`, `hnuser` or `age` classes, and comments
+    /// identified by their `comhead`.
+    Archived,
+}
+
+fn sel(s: &str) -> Result<Selector, ParseError> {
+    Selector::parse(s).map_err(|e| {
+        ParseError::new(format!("Invalid selector '{}': {:?}", s, e))
+    })
 }
 
 fn el_text(el: &ElementRef) -> String {
@@ -33,7 +49,7 @@ fn el_text(el: &ElementRef) -> String {
             let clean = t.trim();
             if !clean.is_empty() {
                 s.push(' ');
-                s.push_str(&clean);
+                s.push_str(clean);
             }
             s
         })
@@ -50,27 +66,57 @@ fn el_text_opt(el: &ElementRef) -> Option<String> {
     }
 }
 
+/// Extract all link targets from an HTML fragment.
+pub(crate) fn parse_links(html: &str) -> Result<Vec<String>, ParseError> {
+    let links = Document::parse_fragment(html)
+        .select(&sel("a[href]")?)
+        .filter_map(|a| a.value().attr("href"))
+        .map(|href| href.to_string())
+        .collect();
+    Ok(links)
+}
+
 fn parse_username(el: ElementRef) -> Result<String, ParseError> {
-    el.select(&sel(".hnuser"))
+    el.select(&sel(".hnuser")?)
         .next()
+        // Archived pages have plain profile links.
+        .or_else(|| el.select(&sel("a[href^='user?id=']").ok()?).next())
         .and_then(|el| el_text_opt(&el))
         .ok_or_else(|| ParseError::new("Could not find username"))
 }
 
+/// Parent element of `el`.
+fn el_parent<'a>(el: ElementRef<'a>) -> Option<ElementRef<'a>> {
+    el.parent().and_then(ElementRef::wrap)
+}
+
+/// Id of the first item link in `el`.
+fn parse_item_link_id(el: ElementRef) -> Option<String> {
+    el.select(&sel("a[href^='item?id=']").ok()?)
+        .filter_map(|a| a.value().attr("href"))
+        .map(|href| href["item?id=".len()..].split('&').next().unwrap_or(""))
+        .find(|id| !id.is_empty())
+        .map(|id| id.to_string())
+}
+
 type Url = String;
 type Title = String;
 
-fn parse_storylink(el: ElementRef) -> Result<(Title, Url), ParseError> {
+/// The title and link of a row; self posts like Ask HN have no link.
+fn parse_storylink(el: ElementRef) -> Result<(Title, Option<Url>), ParseError> {
     let storylink = el
-        .select(&sel(".storylink"))
+        .select(&sel(".storylink")?)
         .next()
+        // Archived pages have an unclassed link in the title cell.
+        .or_else(|| el.select(&sel("td.title > a").ok()?).next())
         .ok_or_else(|| ParseError::new("Could not find story link"))?;
 
     let url = storylink
         .value()
         .attr("href")
-        .ok_or_else(|| ParseError::new("Story link has no href"))?
-        .to_string();
+        .ok_or_else(|| ParseError::new("Story link has no href"))?;
+    // Self posts link to their own discussion page.
+    let url = Some(url.to_string()).filter(|url| !url.starts_with("item?id="));
 
     let title = el_text_opt(&storylink)
         .ok_or_else(|| ParseError::new("Could not find title"))?;
@@ -79,8 +125,9 @@ fn parse_storylink(el: ElementRef) -> Result<(Title, Url), ParseError> {
 }
 
 fn parse_score(el: ElementRef) -> Result<u64, ParseError> {
-    el.select(&sel(".score"))
+    el.select(&sel(".score")?)
         .next()
+        .or_else(|| el.select(&sel("span[id^='score_']").ok()?).next())
         .and_then(|el| {
             el_text(&el)
                 .split(' ')
@@ -92,9 +139,13 @@ fn parse_score(el: ElementRef) -> Result<u64, ParseError> {
 
 fn parse_comment_count(el: ElementRef) -> Result<u64, ParseError> {
     let text = el
-        .select(&sel("a"))
+        .select(&sel("a")?)
         .map(|a| el_text(&a))
-        .filter(|txt| txt.ends_with("comments") || txt == "discuss")
+        .filter(|txt| {
+            txt.ends_with("comments")
+                || txt.ends_with("comment")
+                || txt == "discuss"
+        })
         .last()
         .ok_or_else(|| ParseError::new("Could not find comment count"))?;
 
@@ -111,72 +162,285 @@ fn parse_comment_count(el: ElementRef) -> Result<u64, ParseError> {
     }
 }
 
+/// Unix timestamp of an ISO 8601 UTC date like `2023-10-05T14:03:11`.
+fn parse_iso_datetime(value: &str) -> Option<u64> {
+    let (date, time) = (value.get(..10)?, value.get(11..19)?);
+    let mut date = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let mut time = time.split(':').map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // Days since 1970-01-01, from Howard Hinnant's `days_from_civil`.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era =
+        year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let seconds = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    Some(seconds as u64).filter(|_| seconds >= 0)
+}
+
+/// Timestamp of an `.age` element.
+///
+/// HN puts the UTC time in the title, optionally followed by the Unix
+/// timestamp: `2023-10-05T14:03:11 1696514591`.
+fn parse_age_timestamp(age: &ElementRef) -> Option<u64> {
+    let title = age.value().attr("title")?;
+    let mut parts = title.split_whitespace();
+    let iso = parts.next()?;
+    parts
+        .next()
+        .and_then(|unix| unix.parse().ok())
+        .or_else(|| parse_iso_datetime(iso))
+}
+
+/// Job postings are the only posts without a score and submitter.
+fn post_kind(score: Option<u64>, username: &Option<String>) -> PostKind {
+    match (score, username) {
+        (None, None) => PostKind::Job,
+        _ => PostKind::Story,
+    }
+}
+
+/// Parse the subtext line of a post.
+///
+/// `title_el` holds the title, which on listings is a separate row.
+fn parse_subtext(
+    title_el: ElementRef,
+    el: ElementRef,
+) -> Result<(PostMeta, PostActions), ParseError> {
+    let href = |a: &ElementRef| a.value().attr("href").map(String::from);
+
+    let (age, age_url, timestamp) = match el.select(&sel(".age")?).next() {
+        Some(age) => (
+            el_text_opt(&age),
+            age.select(&sel("a")?).next().and_then(|a| href(&a)),
+            parse_age_timestamp(&age),
+        ),
+        None => (None, None, None),
+    };
+    let meta = PostMeta {
+        score_id: el
+            .select(&sel("span[id^='score_']")?)
+            .next()
+            .and_then(|span| span.value().attr("id"))
+            .map(String::from),
+        user_url: el
+            .select(&sel("a[href^='user?id=']")?)
+            .next()
+            .and_then(|a| href(&a)),
+        age,
+        age_url,
+        timestamp,
+        site: title_el
+            .select(&sel(".sitestr")?)
+            .next()
+            .and_then(|span| el_text_opt(&span)),
+    };
+
+    let mut actions = PostActions::default();
+    for a in el.select(&sel("a")?) {
+        let slot = match el_text(&a).as_str() {
+            "hide" | "un-hide" => &mut actions.hide,
+            "past" => &mut actions.past,
+            "flag" | "unflag" => &mut actions.flag,
+            "favorite" | "un-favorite" => &mut actions.favorite,
+            "web" => &mut actions.web,
+            _ => continue,
+        };
+        *slot = href(&a);
+    }
+
+    Ok((meta, actions))
+}
+
 fn parse_upvote(el: ElementRef) -> Option<VoteAction> {
     let a = el
-        .select(&sel("a"))
+        .select(&sel("a").ok()?)
         .find(|el| el.value().attr("href").unwrap_or("").contains("how=up"))
         .filter(|el| {
             !el.value().attr("class").unwrap_or("").contains("nosee")
         })?;
 
-    let url = a.value().attr("href").unwrap().to_string();
+    let url = a.value().attr("href")?.to_string();
     Some(VoteAction::Upvote(url))
 }
 
 fn parse_downvote(el: ElementRef) -> Option<VoteAction> {
     let a = el
-        .select(&sel("a"))
+        .select(&sel("a").ok()?)
         .find(|el| el.value().attr("href").unwrap_or("").contains("how=un"))?;
-    let url = a.value().attr("href").unwrap().to_string();
+    let url = a.value().attr("href")?.to_string();
     Some(VoteAction::Downvote(url))
 }
 
-pub fn parse_list(doc: Document) -> Result<Vec<Post>, ParseError> {
-    doc.select(&sel(".athing"))
-        .map(|row_ref| -> Result<_, _> {
-            let row = row_ref.value();
+fn parse_vote_availability(els: &[ElementRef]) -> VoteAvailability {
+    let mut votes = VoteAvailability::default();
+    let links = match sel("a") {
+        Ok(links) => links,
+        Err(_) => return votes,
+    };
 
-            let id = row
-                .attr("id")
-                .ok_or_else(|| {
-                    ParseError::new("Could not get id for submission")
-                })?
-                .to_string();
+    for el in els {
+        for a in el.select(&links) {
+            let href = a.value().attr("href").unwrap_or("");
+            let visible =
+                !a.value().attr("class").unwrap_or("").contains("nosee");
+            if href.contains("how=up") {
+                votes.can_up |= visible;
+            } else if href.contains("how=down") {
+                votes.can_down |= visible;
+            } else if href.contains("how=un") {
+                votes.already_voted = true;
+            }
+        }
+    }
+
+    votes
+}
+
+fn parse_cursor(href: &str) -> Option<Cursor> {
+    let query = href.split_once('?')?.1;
 
+    let mut page = None;
+    let mut next = None;
+    let mut offset = None;
+    for pair in query.split('&') {
+        let mut kv = pair.splitn(2, '=');
+        match (kv.next(), kv.next()) {
+            (Some("p"), Some(v)) => page = v.parse::<u64>().ok(),
+            (Some("next"), Some(v)) => next = Some(v.to_string()),
+            (Some("n"), Some(v)) => offset = v.parse::<u64>().ok(),
+            _ => {}
+        }
+    }
+
+    let href = href.to_string();
+    match (next, offset, page) {
+        (Some(next), Some(offset), _) => {
+            Some(Cursor::Next { href, next, offset })
+        }
+        (_, _, Some(page)) => Some(Cursor::Page { href, page }),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_more_link(doc: &Document) -> Option<Cursor> {
+    doc.select(&sel("a.morelink").ok()?)
+        .next()
+        .and_then(|el| el.value().attr("href"))
+        .and_then(parse_cursor)
+}
+
+pub(crate) fn parse_list(doc: Document) -> Result<Page<Post>, ParseError> {
+    parse_list_layout(doc, Layout::Current)
+}
+
+/// Parse a listing from raw HTML, eg. a saved or archived copy.
+pub fn parse_list_html(
+    html: &str,
+    layout: Layout,
+) -> Result<Page<Post>, ParseError> {
+    parse_list_layout(Document::parse_document(html), layout)
+}
+
+/// Rows holding the story links of a listing.
+fn list_rows<'a>(
+    doc: &'a Document,
+    layout: Layout,
+) -> Result<Vec<ElementRef<'a>>, ParseError> {
+    let rows = match layout {
+        Layout::Current => doc.select(&sel(".athing")?).collect(),
+        Layout::Archived => doc
+            .select(&sel("td.title > a")?)
+            .filter(|link| el_text(link) != "More")
+            .filter_map(|link| el_parent(link).and_then(el_parent))
+            .filter(|row| row.value().name() == "tr")
+            .collect(),
+    };
+    Ok(rows)
+}
+
+fn parse_list_layout(
+    doc: Document,
+    layout: Layout,
+) -> Result<Page<Post>, ParseError> {
+    // Out-of-range pages still render an (empty) item list, so a missing
+    // list means the page is not a listing at all.
+    if layout == Layout::Current
+        && doc.select(&sel(".itemlist")?).next().is_none()
+    {
+        return Err(ParseError::new("Could not find item list"));
+    }
+
+    let items = list_rows(&doc, layout)?
+        .into_iter()
+        .map(|row_ref| -> Result<_, _> {
             let (title, url) = parse_storylink(row_ref)?;
 
             let action_row_ref = row_ref
-                .next_sibling()
-                .and_then(|node| ElementRef::wrap(node))
+                .next_siblings()
+                .find_map(ElementRef::wrap)
                 .ok_or_else(|| ParseError::new("Could not find action row"))?;
 
+            let id = row_ref
+                .value()
+                .attr("id")
+                .map(|id| id.to_string())
+                .or_else(|| parse_item_link_id(action_row_ref))
+                .ok_or_else(|| {
+                    ParseError::new("Could not get id for submission")
+                })?;
+
             let upvote = parse_upvote(row_ref);
             let downvote = parse_downvote(action_row_ref);
             let vote = upvote.or(downvote);
+            let votes = parse_vote_availability(&[row_ref, action_row_ref]);
 
             let comment_count =
                 parse_comment_count(action_row_ref).unwrap_or(0);
-            let score = parse_score(action_row_ref).unwrap_or(0);
-            let username = parse_username(action_row_ref)
-                .unwrap_or("<unknown>".to_string());
+            let score = parse_score(action_row_ref).ok();
+            let username = parse_username(action_row_ref).ok();
+            let kind = post_kind(score, &username);
+            let (meta, actions) = parse_subtext(row_ref, action_row_ref)?;
 
             Ok(Post {
                 id,
                 title,
                 username,
                 url,
+                kind,
                 score,
                 comment_count,
-                comments: Vec::new(),
+                comments: Ordered::default(),
                 vote,
+                votes,
+                meta,
+                actions,
+                enrichments: Vec::new(),
+                truncated: None,
+                partial: false,
             })
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let next = parse_more_link(&doc);
+    let is_last = items.is_empty() || next.is_none();
+
+    Ok(Page {
+        items: Ordered::new(items),
+        next,
+        is_last,
+    })
 }
 
 fn parse_comment(el: ElementRef) -> Result<Comment, ParseError> {
-    let username = parse_username(el)?;
-
     let id = el
         .value()
         .attr("id")
@@ -184,32 +448,80 @@ fn parse_comment(el: ElementRef) -> Result<Comment, ParseError> {
         .to_string();
 
     let depth = el
-        .select(&sel(".ind img"))
+        .select(&sel(".ind img")?)
         .next()
         .and_then(|el| el.value().attr("width"))
         .and_then(|width| width.parse::<u32>().ok())
         .map(|width| width / 40)
         .ok_or_else(|| ParseError::new("Could not determine comment depth"))?;
 
+    parse_comment_body(el, id, depth)
+}
+
+/// Parse a comment of an archived thread, given its `td.default` cell.
+fn parse_archived_comment(cell: ElementRef) -> Result<Comment, ParseError> {
+    let row = el_parent(cell)
+        .ok_or_else(|| ParseError::new("Could not find comment row"))?;
+
+    let id = cell
+        .select(&sel(".comhead")?)
+        .next()
+        .and_then(parse_item_link_id)
+        .ok_or_else(|| ParseError::new("Could not determine comment id"))?;
+
+    let depth = row
+        .select(&sel("img[src$='s.gif']")?)
+        .next()
+        .and_then(|el| el.value().attr("width"))
+        .and_then(|width| width.parse::<u32>().ok())
+        .map(|width| width / 40)
+        .unwrap_or(0);
+
+    parse_comment_body(row, id, depth)
+}
+
+/// Parse the parts of a comment shared by thread rows and comment pages.
+fn parse_comment_body(
+    el: ElementRef,
+    id: String,
+    depth: u32,
+) -> Result<Comment, ParseError> {
+    let username = parse_username(el)?;
+
+    let timestamp = el
+        .select(&sel(".age")?)
+        .next()
+        .and_then(|el| parse_age_timestamp(&el));
     let age = el
-        .select(&sel(".age"))
+        .select(&sel(".age")?)
         .next()
         .and_then(|el| el_text_opt(&el))
+        // Archived pages: "<user> 3 hours ago | link".
+        .or_else(|| {
+            let head = el_text(&el.select(&sel(".comhead").ok()?).next()?);
+            let age = head
+                .trim_start_matches(username.as_str())
+                .split('|')
+                .next()?
+                .trim();
+            Some(age.to_string()).filter(|age| !age.is_empty())
+        })
         .ok_or_else(|| ParseError::new("Could not find comment age"))?;
 
-    let content_html =
-        el.select(&sel(".comment"))
-            .next()
-            .map(|el| el.html())
-            .ok_or_else(|| ParseError::new("Could not find comment text"))?;
+    let content_html = el
+        .select(&sel(".comment")?)
+        .next()
+        .map(|el| el.html())
+        .ok_or_else(|| ParseError::new("Could not find comment text"))?;
 
+    let link_sel = sel("a")?;
     let (upvote, downvote) = el
-        .select(&sel(".votelinks"))
+        .select(&sel(".votelinks")?)
         .next()
         .map(|el| {
             let mut up = None;
             let mut down = None;
-            for link in el.select(&sel("a")) {
+            for link in el.select(&link_sel) {
                 if let Some(href) = link.value().attr("href") {
                     if href.contains("how=up") {
                         up = Some(VoteAction::Upvote(href.to_string()));
@@ -221,47 +533,567 @@ fn parse_comment(el: ElementRef) -> Result<Comment, ParseError> {
             (up, down)
         })
         .unwrap_or((None, None));
+    let votes = parse_vote_availability(&[el]);
 
     Ok(Comment {
         id,
         depth,
         age,
+        timestamp,
         username,
         content_html,
         children: Vec::new(),
         upvote,
         downvote,
+        votes,
+        author_profile: None,
     })
 }
 
-pub fn parse_submission(id: String, dom: Document) -> Result<Post, ParseError> {
+/// Parse a comment's own page.
+///
+/// Returns the comment and its descendants in thread order, with depths
+/// relative to the comment.
+pub(crate) fn parse_comment_page(
+    dom: &Document,
+) -> Result<(Comment, Vec<Comment>), ParseError> {
     let header = dom
-        .select(&sel(".fatitem"))
+        .select(&sel(".fatitem")?)
+        .next()
+        .ok_or_else(|| ParseError::new("Could not find comment header"))?;
+    let id = header
+        .select(&sel(".athing")?)
+        .next()
+        .and_then(|row| row.value().attr("id"))
+        .ok_or_else(|| ParseError::new("Could not determine comment id"))?
+        .to_string();
+    let comment = parse_comment_body(header, id, 0)?;
+
+    // Direct replies are not indented on a comment page.
+    let descendants = dom
+        .select(&sel(".comment-tree .athing.comtr")?)
+        .map(|el| {
+            parse_comment(el).map(|mut comment| {
+                comment.depth += 1;
+                comment
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((comment, descendants))
+}
+
+/// Id of the parent of the comment on a comment page, unless the parent is
+/// the story itself.
+pub(crate) fn parse_parent_comment_id(dom: &Document) -> Option<String> {
+    let header = dom.select(&sel(".fatitem").ok()?).next()?;
+    let id_of = |a: ElementRef| -> Option<String> {
+        let href = a.value().attr("href")?;
+        Some(
+            href.strip_prefix("item?id=")?
+                .split('&')
+                .next()?
+                .to_string(),
+        )
+    };
+
+    let parent = header
+        .select(&sel("a").ok()?)
+        .find(|a| el_text(a) == "parent")
+        .and_then(id_of)?;
+    let story = header
+        .select(&sel(".onstory a").ok()?)
+        .next()
+        .and_then(id_of);
+    if story.as_ref() == Some(&parent) {
+        None
+    } else {
+        Some(parent)
+    }
+}
+
+/// Why an item page holds no item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Missing {
+    NotFound,
+    Deleted,
+    /// A stub for a dead story, served unless `showdead` is enabled.
+    Dead,
+    /// A stub for a flagged story, served unless `showdead` is enabled.
+    Flagged,
+}
+
+/// Detect HN's pages for nonexistent, deleted and hidden items.
+pub(crate) fn detect_missing_item(dom: &Document) -> Option<Missing> {
+    let header = match dom.select(&sel(".fatitem").ok()?).next() {
+        Some(header) => header,
+        None => {
+            let body = dom.select(&sel("body").ok()?).next()?;
+            return Some(Missing::NotFound)
+                .filter(|_| el_text(&body) == "No such item.");
+        }
+    };
+    let has_author = header.select(&sel(".hnuser").ok()?).next().is_some();
+    if !has_author && el_text(&header).contains("[deleted]") {
+        return Some(Missing::Deleted);
+    }
+    // Stubs keep the title cell, with a marker in place of the link.
+    let title = header.select(&sel("td.title").ok()?).next()?;
+    if title.select(&sel("a").ok()?).next().is_some() {
+        return None;
+    }
+    let title = el_text(&title);
+    if title.contains("[flagged]") {
+        Some(Missing::Flagged)
+    } else if title.contains("[dead]") {
+        Some(Missing::Dead)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn parse_submission(
+    id: String,
+    dom: Document,
+) -> Result<Post, ParseError> {
+    parse_submission_layout(id, dom, Layout::Current)
+}
+
+/// Parse a thread from raw HTML, eg. a saved or archived copy.
+pub fn parse_submission_html(
+    id: &str,
+    html: &str,
+    layout: Layout,
+) -> Result<Post, ParseError> {
+    parse_submission_layout(
+        id.to_string(),
+        Document::parse_document(html),
+        layout,
+    )
+}
+
+fn parse_submission_layout(
+    id: String,
+    dom: Document,
+    layout: Layout,
+) -> Result<Post, ParseError> {
+    parse_full_submission(id.clone(), &dom, layout)
+        .or_else(|err| parse_title_fallback(id, &dom).ok_or(err))
+}
+
+/// Recover a partial post from the `<title>` tag, `<story> | Hacker News`.
+fn parse_title_fallback(id: String, dom: &Document) -> Option<Post> {
+    let title = dom
+        .select(&sel("head > title").ok()?)
         .next()
-        .ok_or_else(|| ParseError::new("Could not find post header"))?;
+        .and_then(|el| el_text_opt(&el))?;
+    let title = title.strip_suffix(" | Hacker News")?.trim();
+    if title.is_empty() {
+        return None;
+    }
+    let mut post = Post::new(id, title);
+    post.partial = true;
+    Some(post)
+}
+
+fn parse_full_submission(
+    id: String,
+    dom: &Document,
+    layout: Layout,
+) -> Result<Post, ParseError> {
+    let header = match layout {
+        Layout::Current => dom.select(&sel(".fatitem")?).next(),
+        // The table around the title row.
+        Layout::Archived => dom
+            .select(&sel("td.title")?)
+            .next()
+            .and_then(el_parent)
+            .and_then(el_parent)
+            .filter(|el| el.value().name() != "tr"),
+    }
+    .ok_or_else(|| ParseError::new("Could not find post header"))?;
 
     let (title, url) = parse_storylink(header)?;
-    let username = parse_username(header)?;
-    let score = parse_score(header)?;
+    let username = parse_username(header).ok();
+    let score = parse_score(header).ok();
+    let kind = post_kind(score, &username);
 
     let upvote = parse_upvote(header);
     let downvote = parse_downvote(header);
     let vote = upvote.or(downvote);
-    let comment_count = parse_comment_count(header)?;
+    let votes = parse_vote_availability(&[header]);
+    let comment_count = match kind {
+        PostKind::Story => parse_comment_count(header)?,
+        PostKind::Job => 0,
+    };
+    let (meta, actions) = parse_subtext(header, header)?;
 
-    let comments = dom
-        .select(&sel(".comment-tree .athing.comtr"))
-        .map(parse_comment)
-        .collect::<Result<Vec<_>, _>>()?;
+    let comments = match layout {
+        Layout::Current => dom
+            .select(&sel(".comment-tree .athing.comtr")?)
+            .map(parse_comment)
+            .collect::<Result<Vec<_>, _>>()?,
+        Layout::Archived => dom
+            .select(&sel("td.default")?)
+            .map(parse_archived_comment)
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    let truncated = parse_more_link(dom).map(|cursor| MoreComments {
+        url: cursor.href().to_string(),
+        remaining: comment_count.saturating_sub(comments.len() as u64),
+    });
 
     Ok(Post {
         id,
         title,
         url,
+        kind,
         username,
         score,
         comment_count,
-        comments,
+        comments: Ordered::new(comments),
         vote,
+        votes,
+        meta,
+        actions,
+        enrichments: Vec::new(),
+        truncated,
+        partial: false,
+    })
+}
+
+/// Parse the `key: value` rows of a profile page.
+fn parse_profile_rows(
+    doc: &Document,
+) -> Result<Vec<(String, ElementRef<'_>)>, ParseError> {
+    let rows = doc
+        .select(&sel("tr")?)
+        .filter_map(|row| {
+            let cells = row
+                .children()
+                .filter_map(ElementRef::wrap)
+                .filter(|el| el.value().name() == "td")
+                .collect::<Vec<_>>();
+            if cells.len() != 2 {
+                return None;
+            }
+            let key = el_text(&cells[0]);
+            if key.ends_with(':') {
+                Some((key.trim_end_matches(':').to_string(), cells[1]))
+            } else {
+                None
+            }
+        })
+        .collect();
+    Ok(rows)
+}
+
+pub(crate) fn parse_user(doc: &Document) -> Result<User, ParseError> {
+    let rows = parse_profile_rows(doc)?;
+    let value = |key: &str| {
+        rows.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, el)| *el)
+            .ok_or_else(|| {
+                ParseError::new(format!("Could not find profile {}", key))
+            })
+    };
+
+    let username = el_text_opt(&value("user")?)
+        .ok_or_else(|| ParseError::new("Could not find username"))?;
+    let created = el_text(&value("created")?);
+    let karma = el_text(&value("karma")?).parse::<u64>().map_err(|e| {
+        ParseError::new(format!("Could not parse karma: {}", e))
+    })?;
+    let about_html = value("about")
+        .ok()
+        .map(|el| el.inner_html().trim().to_string())
+        .filter(|html| !html.is_empty());
+
+    Ok(User {
+        username,
+        created,
+        karma,
+        about_html,
     })
 }
+
+/// Read the `showdead` setting from the logged in user's own profile page.
+pub(crate) fn parse_showdead(doc: &Document) -> Option<bool> {
+    doc.select(&sel("select[name=showd] option[selected]").ok()?)
+        .next()
+        .map(|el| el_text(&el) == "yes")
+}
+
+/// Check if a page contains an HN action form (identified by its `fnid`).
+pub(crate) fn has_fnid_form(doc: &Document) -> bool {
+    sel("form input[name=fnid]")
+        .map(|inputs| doc.select(&inputs).next().is_some())
+        .unwrap_or(false)
+}
+
+/// An HN action form.
+pub(crate) struct Form {
+    pub action: String,
+    /// Hidden inputs, like `fnid`, to submit back unchanged.
+    pub hidden: Vec<(String, String)>,
+    /// Names of the visible inputs and textareas, in document order.
+    pub fields: Vec<String>,
+}
+
+/// Parse the first form containing an `fnid` input.
+pub(crate) fn parse_fnid_form(doc: &Document) -> Result<Form, ParseError> {
+    let form = doc
+        .select(&sel("form")?)
+        .find(|form| {
+            sel("input[name=fnid]")
+                .map(|fnid| form.select(&fnid).next().is_some())
+                .unwrap_or(false)
+        })
+        .ok_or_else(|| ParseError::new("Could not find form"))?;
+
+    let action = form
+        .value()
+        .attr("action")
+        .ok_or_else(|| ParseError::new("Form has no action"))?
+        .trim_start_matches('/')
+        .to_string();
+
+    let mut hidden = Vec::new();
+    let mut fields = Vec::new();
+    for el in form.select(&sel("input, textarea")?) {
+        let value = el.value();
+        let name = match value.attr("name") {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        match value.attr("type") {
+            Some("hidden") => hidden
+                .push((name, value.attr("value").unwrap_or("").to_string())),
+            Some("submit") => {}
+            _ => fields.push(name),
+        }
+    }
+
+    Ok(Form {
+        action,
+        hidden,
+        fields,
+    })
+}
+
+/// Inputs to [fuzz_entry] are truncated to this many bytes.
+pub const FUZZ_MAX_INPUT: usize = 1024 * 1024;
+
+/// Run all page parsers over arbitrary bytes, discarding the results.
+///
+/// Meant for fuzzers: it must never panic. Input is truncated to
+/// [FUZZ_MAX_INPUT] bytes to bound memory use.
+pub fn fuzz_entry(bytes: &[u8]) {
+    let bytes = &bytes[..bytes.len().min(FUZZ_MAX_INPUT)];
+    let html = String::from_utf8_lossy(bytes);
+
+    let _ = parse_list(Document::parse_document(&html));
+    let _ = parse_submission(String::new(), Document::parse_document(&html));
+    let _ = parse_links(&html);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{list_fixture, thread_fixture};
+
+    #[test]
+    fn test_parse_truncated_thread() {
+        let html = thread_fixture(&[(2, 0), (3, 1)]);
+        let post =
+            parse_submission("1".into(), Document::parse_document(&html))
+                .unwrap();
+        assert_eq!(post.truncated, None);
+
+        let html = html.replace("2&nbsp;comments", "10&nbsp;comments").replace(
+            "</body>",
+            "<a class='morelink' href='item?id=1&amp;p=2'>More</a></body>",
+        );
+        let post =
+            parse_submission("1".into(), Document::parse_document(&html))
+                .unwrap();
+        assert_eq!(
+            post.truncated,
+            Some(MoreComments {
+                url: "item?id=1&p=2".into(),
+                remaining: 8,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_archived_layout() {
+        let list = "<html><body><table>\
+            <tr><td class='title'>1.</td><td class='title'>\
+            <a href='http://example.com/a'>Story A</a></td></tr>\
+            <tr><td></td><td class='subtext'>\
+            <span id='score_11'>12 points</span> by \
+            <a href='user?id=alice'>alice</a> 2 hours ago | \
+            <a href='item?id=11'>3 comments</a></td></tr>\
+            <tr><td></td><td class='title'>\
+            <a href='x?fnid=abc'>More</a></td></tr>\
+            </table></body></html>";
+        let page = parse_list_html(list, Layout::Archived).unwrap();
+        assert_eq!(page.items.len(), 1);
+        let post = &page.items[0];
+        assert_eq!(post.id, "11");
+        assert_eq!(post.title, "Story A");
+        assert_eq!(post.username.as_deref(), Some("alice"));
+        assert_eq!(post.score, Some(12));
+        assert_eq!(post.comment_count, 3);
+
+        let thread = "<html><body><table><tr><td class='title'>\
+            <a href='http://example.com/a'>Story A</a></td></tr>\
+            <tr><td class='subtext'><span id='score_11'>12 points</span> \
+            by <a href='user?id=alice'>alice</a> | \
+            <a href='item?id=11'>1 comment</a></td></tr></table>\
+            <table><tr><td><img src='s.gif' width='40'></td>\
+            <td class='default'><span class='comhead'>\
+            <a href='user?id=bob'>bob</a> 1 hour ago | \
+            <a href='item?id=12'>link</a></span>\
+            <span class='comment'>Hello</span></td></tr></table>\
+            </body></html>";
+        let post =
+            parse_submission_html("11", thread, Layout::Archived).unwrap();
+        assert_eq!(post.title, "Story A");
+        assert_eq!(post.comments.len(), 1);
+        let comment = &post.comments[0];
+        assert_eq!(comment.id, "12");
+        assert_eq!(comment.username, "bob");
+        assert_eq!(comment.depth, 1);
+        assert_eq!(comment.age, "1 hour ago");
+    }
+
+    #[test]
+    fn test_parse_partial_submission() {
+        let html = "<html><head><title>Show HN: A thing | Hacker News\
+                    </title></head><body><table></table></body></html>";
+        let post = parse_submission("5".into(), Document::parse_document(html))
+            .unwrap();
+        assert!(post.partial);
+        assert_eq!(post.id, "5");
+        assert_eq!(post.title, "Show HN: A thing");
+
+        let html = "<html><body>No such item.</body></html>";
+        assert!(parse_submission("5".into(), Document::parse_document(html))
+            .is_err());
+    }
+
+    #[test]
+    fn test_detect_missing_item() {
+        let doc = |html: &str| Document::parse_document(html);
+        assert_eq!(
+            detect_missing_item(&doc("No such item.")),
+            Some(Missing::NotFound)
+        );
+        assert_eq!(
+            detect_missing_item(&doc(
+                "<table class='fatitem'><tr class='athing' id='2'>\
+                 <td class='default'><span class='comhead'>\
+                 <span class='age'><a>1 hour ago</a></span></span>\
+                 <div class='comment'>[deleted]</div></td></tr></table>"
+            )),
+            Some(Missing::Deleted)
+        );
+        let html = thread_fixture(&[(2, 0)]);
+        assert_eq!(detect_missing_item(&doc(&html)), None);
+    }
+
+    #[test]
+    fn test_parse_subtext() {
+        let html = list_fixture(&[7]).replace(
+            "</td></tr></table>",
+            " | <span class='age'><a href='item?id=7'>1 hour ago</a></span> \
+             | <a href='hide?id=7&amp;goto=news'>hide</a> \
+             | <a href='https://hn.algolia.com/?query=x'>past</a>\
+             </td></tr></table>",
+        );
+        let page = parse_list(Document::parse_document(&html)).unwrap();
+        let post = &page.items[0];
+        assert_eq!(post.meta.age.as_deref(), Some("1 hour ago"));
+        assert_eq!(post.meta.age_url.as_deref(), Some("item?id=7"));
+        assert_eq!(post.actions.hide.as_deref(), Some("hide?id=7&goto=news"));
+        assert_eq!(
+            post.actions.past.as_deref(),
+            Some("https://hn.algolia.com/?query=x")
+        );
+        assert_eq!(post.actions.flag, None);
+    }
+
+    #[test]
+    fn test_parse_list_keeps_rank_order() {
+        // Simple deterministic permutations of ids.
+        for len in 0..40u64 {
+            let ids = (0..len)
+                .map(|i| (i * 7919 + len * 31) % 1000 + 1)
+                .collect::<Vec<_>>();
+            let html = list_fixture(&ids);
+            let page = parse_list(Document::parse_document(&html)).unwrap();
+            let parsed = page
+                .items
+                .iter()
+                .map(|post| post.id.parse::<u64>().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(parsed, ids);
+        }
+    }
+
+    #[test]
+    fn test_parse_thread_keeps_display_order() {
+        for len in 0..40u64 {
+            // A valid pre-order depth sequence: each depth is at most one
+            // deeper than the previous comment.
+            let mut depth = 0u32;
+            let comments = (0..len)
+                .map(|i| {
+                    let id = (i * 104_729 + len) % 10_000 + 2;
+                    let entry = (id, depth);
+                    depth = match (i * 13 + len) % 3 {
+                        0 => depth + 1,
+                        1 => depth,
+                        _ => 0,
+                    };
+                    entry
+                })
+                .collect::<Vec<_>>();
+            let html = thread_fixture(&comments);
+            let post =
+                parse_submission("1".into(), Document::parse_document(&html))
+                    .unwrap();
+            let parsed = post
+                .comments
+                .iter()
+                .map(|c| (c.id.parse::<u64>().unwrap(), c.depth))
+                .collect::<Vec<_>>();
+            assert_eq!(parsed, comments);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_entry_malformed() {
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"\xff\xfe<table",
+            b"<table class=itemlist><tr class=athing id=1><td>\
+              <a class=storylink>x</a><a href=x?how=up",
+            b"<table class=itemlist><tr class=athing><td>\
+              <a class=storylink href=x>t</a></td></tr>\
+              <tr><td><a>how=up</a><span class=score>nan points",
+            b"<div class=fatitem><a class=storylink href=y>t</a>\
+              <a class=hnuser>u</a><span class=score>1 point</span>\
+              <a>discuss</a></div><table class=comment-tree>\
+              <tr class='athing comtr' id=2><td class=ind>\
+              <img width=abc></td></tr></table>",
+        ];
+        for input in inputs {
+            fuzz_entry(input);
+        }
+    }
+}
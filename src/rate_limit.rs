@@ -0,0 +1,106 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Client-side request throttling.
+///
+/// HN bans IPs that fetch too aggressively; a limit keeps the client
+/// within polite bounds without the caller having to pace requests.
+#[non_exhaustive]
+#[derive(Clone, Debug, Default)]
+pub struct RateLimit {
+    /// Minimum time between the start of two requests.
+    pub min_delay: Option<Duration>,
+    /// Maximum number of requests in any 60 second window.
+    pub per_minute: Option<u32>,
+}
+
+impl RateLimit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn min_delay(mut self, delay: Duration) -> Self {
+        self.min_delay = Some(delay);
+        self
+    }
+
+    pub fn per_minute(mut self, requests: u32) -> Self {
+        self.per_minute = Some(requests);
+        self
+    }
+}
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    /// Start times of the requests in the current window.
+    recent: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            recent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Block until another request may be sent.
+    pub fn acquire(&self) {
+        let mut recent = self
+            .recent
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if let (Some(delay), Some(last)) = (self.limit.min_delay, recent.back())
+        {
+            let elapsed = last.elapsed();
+            if elapsed < delay {
+                std::thread::sleep(delay - elapsed);
+            }
+        }
+
+        if let Some(max) = self.limit.per_minute {
+            let max = max.max(1) as usize;
+            while recent.front().is_some_and(|t| t.elapsed() >= WINDOW) {
+                recent.pop_front();
+            }
+            if recent.len() >= max {
+                let oldest = recent[recent.len() - max];
+                let elapsed = oldest.elapsed();
+                if elapsed < WINDOW {
+                    std::thread::sleep(WINDOW - elapsed);
+                }
+            }
+        }
+
+        recent.push_back(Instant::now());
+        // Only the last `per_minute` entries are ever inspected.
+        let keep = self.limit.per_minute.unwrap_or(1).max(1) as usize;
+        while recent.len() > keep {
+            recent.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit() {
+        let limiter = RateLimiter::new(
+            RateLimit::new().min_delay(Duration::from_millis(50)),
+        );
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use super::error::Error;
+
+/// Redirects followed per request by default.
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// How redirects are handled.
+///
+/// Some HN endpoints signal their outcome with a redirect, eg. logging in
+/// or submitting a duplicate link. Redirects that are not followed are
+/// returned as is, with the target in [crate::Fetched::location].
+///
+/// Defaults to following up to 10 redirects.
+#[derive(Clone)]
+pub struct RedirectPolicy(Kind);
+
+type Decide = dyn Fn(&str, &str, usize) -> bool + Send + Sync;
+
+#[derive(Clone)]
+enum Kind {
+    None,
+    Limited(usize),
+    Custom(Arc<Decide>),
+}
+
+impl RedirectPolicy {
+    /// Never follow redirects.
+    pub fn none() -> Self {
+        Self(Kind::None)
+    }
+
+    /// Follow up to `max` redirects, failing with
+    /// [Error::TooManyRedirects] after that.
+    pub fn limited(max: usize) -> Self {
+        Self(Kind::Limited(max))
+    }
+
+    /// Decide for each redirect whether to follow it.
+    ///
+    /// `decide` is called with the redirecting URL, the target and the
+    /// number of redirects followed so far.
+    pub fn custom(
+        decide: impl Fn(&str, &str, usize) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self(Kind::Custom(Arc::new(decide)))
+    }
+
+    /// Whether to follow a redirect from `from` to `to`, after `followed`
+    /// others.
+    pub(crate) fn follow(
+        &self,
+        from: &str,
+        to: &str,
+        followed: usize,
+    ) -> Result<bool, Error> {
+        match &self.0 {
+            Kind::None => Ok(false),
+            Kind::Limited(max) if followed >= *max => {
+                Err(Error::TooManyRedirects { limit: *max })
+            }
+            Kind::Limited(_) => Ok(true),
+            Kind::Custom(decide) => Ok(decide(from, to, followed)),
+        }
+    }
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self::limited(DEFAULT_MAX_REDIRECTS)
+    }
+}
+
+impl std::fmt::Debug for RedirectPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.0 {
+            Kind::None => f.write_str("RedirectPolicy::None"),
+            Kind::Limited(max) => write!(f, "RedirectPolicy::Limited({})", max),
+            Kind::Custom(_) => f.write_str("RedirectPolicy::Custom"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{transport::Response, Client};
+
+    #[test]
+    fn test_redirect_policy() {
+        let url = "http://hn.test/login";
+        let limited = RedirectPolicy::limited(1);
+        assert!(limited.follow(url, "news", 0).unwrap());
+        assert!(matches!(
+            limited.follow(url, "news", 1),
+            Err(Error::TooManyRedirects { limit: 1 })
+        ));
+        assert!(!RedirectPolicy::none().follow(url, "news", 0).unwrap());
+        let custom =
+            RedirectPolicy::custom(|from, _, _| !from.ends_with("/login"));
+        assert!(!custom.follow(url, "news", 0).unwrap());
+
+        let client = Client::try_new().unwrap();
+        let res = Response {
+            status: 302,
+            url: url.into(),
+            redirects: Vec::new(),
+            headers: vec![("Location".into(), "news".into())],
+            body: String::new(),
+        };
+        assert_eq!(res.location(), Some("news"));
+        assert!(client.lands_on(&res, "news"));
+    }
+}
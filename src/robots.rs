@@ -0,0 +1,152 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use super::error::Error;
+
+/// Longest crawl delay honored, so a bogus robots.txt can not stall the
+/// client indefinitely.
+const MAX_CRAWL_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// The `User-Agent: *` rules of a robots.txt file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RobotsPolicy {
+    pub disallow: Vec<String>,
+    pub crawl_delay: Option<Duration>,
+}
+
+impl RobotsPolicy {
+    /// Parse the rules applying to all user agents from a robots.txt file.
+    pub fn parse(robots_txt: &str) -> Self {
+        let mut policy = Self::default();
+        // Whether the current group applies to `*`.
+        let mut applies = false;
+        // Consecutive User-Agent lines belong to the same group.
+        let mut in_agent_lines = false;
+
+        for line in robots_txt.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut parts = line.splitn(2, ':');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => {
+                    (key.trim().to_lowercase(), value.trim())
+                }
+                _ => continue,
+            };
+
+            if key == "user-agent" {
+                if !in_agent_lines {
+                    applies = false;
+                }
+                in_agent_lines = true;
+                applies |= value == "*";
+                continue;
+            }
+            in_agent_lines = false;
+
+            if !applies {
+                continue;
+            }
+            match key.as_str() {
+                "disallow" if !value.is_empty() => {
+                    policy.disallow.push(value.to_string());
+                }
+                "crawl-delay" => {
+                    policy.crawl_delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                        .map(|secs| {
+                            let max = MAX_CRAWL_DELAY.as_secs_f64();
+                            Duration::from_secs_f64(secs.min(max))
+                        });
+                }
+                _ => {}
+            }
+        }
+
+        policy
+    }
+
+    /// Check if a path (relative to the site root) may be crawled.
+    pub fn is_allowed(&self, path: &str) -> bool {
+        let path = if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{}", path)
+        };
+        !self
+            .disallow
+            .iter()
+            .any(|rule| path.starts_with(rule.as_str()))
+    }
+}
+
+/// Enforces a [RobotsPolicy] for a client.
+pub(crate) struct Robots {
+    policy: RobotsPolicy,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl Robots {
+    pub fn new(policy: RobotsPolicy) -> Self {
+        Self {
+            policy,
+            last_request: Mutex::new(None),
+        }
+    }
+
+    /// Refuse disallowed paths and wait out the crawl delay.
+    pub fn acquire(&self, path: &str) -> Result<(), Error> {
+        if !self.policy.is_allowed(path) {
+            return Err(Error::RobotsDisallowed {
+                path: path.to_string(),
+            });
+        }
+
+        // Reserve the next slot under the lock, but wait for it outside,
+        // so other threads can queue up behind this one meanwhile.
+        let now = Instant::now();
+        let slot = {
+            let mut last = self
+                .last_request
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let slot = match (self.policy.crawl_delay, *last) {
+                (Some(delay), Some(last)) => now.max(last + delay),
+                _ => now,
+            };
+            *last = Some(slot);
+            slot
+        };
+        if slot > now {
+            std::thread::sleep(slot - now);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robots_policy() {
+        let policy = RobotsPolicy::parse(
+            "User-Agent: Googlebot\nDisallow: /news\n\n\
+             User-Agent: *\nDisallow: /x?\nDisallow: /vote?\n\
+             Crawl-delay: 30\n",
+        );
+        assert_eq!(policy.crawl_delay, Some(Duration::from_secs(30)));
+        assert!(policy.is_allowed("news?p=2"));
+        assert!(!policy.is_allowed("vote?id=1&how=up"));
+        assert!(!policy.is_allowed("/x?fnid=abc"));
+
+        let inf = RobotsPolicy::parse("User-Agent: *\nCrawl-delay: inf\n");
+        assert_eq!(inf.crawl_delay, None);
+        let huge = RobotsPolicy::parse("User-Agent: *\nCrawl-delay: 1e300\n");
+        assert_eq!(huge.crawl_delay, Some(Duration::from_secs(5 * 60)));
+    }
+}
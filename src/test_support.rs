@@ -0,0 +1,88 @@
+//! Fixtures shared by the unit tests.
+
+use std::sync::Arc;
+
+use super::{
+    transport::{Method, MockTransport, Transport},
+    AuthenticatedClient, Client,
+};
+
+/// A listing page with one story per id, ranked in the given order.
+pub(crate) fn list_fixture(ids: &[u64]) -> String {
+    let rows = ids
+        .iter()
+        .enumerate()
+        .map(|(rank, id)| {
+            format!(
+                "<tr class='athing' id='{id}'><td><span class='rank'>\
+                 {rank}.</span></td><td class='title'>\
+                 <a href='https://example.com/{id}' class='storylink'>\
+                 Story {id}</a></td></tr><tr><td class='subtext'>\
+                 <span class='score'>{id} points</span> by \
+                 <a class='hnuser'>user{id}</a> \
+                 <a href='item?id={id}'>{id}&nbsp;comments</a>\
+                 </td></tr>",
+                id = id,
+                rank = rank + 1,
+            )
+        })
+        .collect::<String>();
+    format!(
+        "<html><body><table class='itemlist'>{}</table></body></html>",
+        rows
+    )
+}
+
+/// The thread of story 1 with the given comments, as (id, depth) pairs.
+pub(crate) fn thread_fixture(comments: &[(u64, u32)]) -> String {
+    let rows = comments
+        .iter()
+        .map(|(id, depth)| {
+            format!(
+                "<tr class='athing comtr' id='{id}'>\
+                 <td class='ind'><img width='{width}'></td>\
+                 <td><span class='comhead'><a class='hnuser'>u{id}</a> \
+                 <span class='age'><a>1 hour ago</a></span></span>\
+                 <div class='comment'><span class='commtext'>\
+                 Comment {id}</span></div></td></tr>",
+                id = id,
+                width = depth * 40,
+            )
+        })
+        .collect::<String>();
+    format!(
+        "<html><body><table class='fatitem'><tr class='athing' id='1'>\
+         <td class='title'><a class='storylink' href='https://x.com'>\
+         Title</a></td></tr><tr><td class='subtext'>\
+         <span class='score'>5 points</span> by <a class='hnuser'>op</a>\
+         <a href='item?id=1'>{}&nbsp;comments</a></td></tr></table>\
+         <table class='comment-tree'>{}</table></body></html>",
+        comments.len(),
+        rows
+    )
+}
+
+/// A client for `http://hn.test` served by the given transport.
+pub(crate) fn mock_client(transport: impl Transport + 'static) -> Client {
+    Client::builder()
+        .base_url("http://hn.test")
+        .transport(Arc::new(transport))
+        .build()
+        .unwrap()
+}
+
+/// Add the responses of a successful login as `u` to the mock.
+pub(crate) fn login_routes(mock: MockTransport) -> MockTransport {
+    mock.page("login?goto=news", "<html></html>").redirect(
+        Method::Post,
+        "login",
+        "news",
+        "<html></html>",
+    )
+}
+
+/// Like [mock_client], logged in as `u`.
+pub(crate) fn mock_login(mock: MockTransport) -> AuthenticatedClient {
+    AuthenticatedClient::login_with(mock_client(login_routes(mock)), "u", "p")
+        .unwrap()
+}
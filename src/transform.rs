@@ -0,0 +1,110 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use super::types::Comment;
+
+/// Rewrites comment content as it is parsed.
+///
+/// Can be used for translation, profanity filtering and similar.
+pub trait TextTransformer: Send + Sync {
+    /// Transform the HTML content of a comment.
+    fn transform(&self, content_html: &str) -> String;
+}
+
+/// Comments whose transformed content is cached.
+const MAX_CACHED_COMMENTS: usize = 10_000;
+
+/// Applies a [TextTransformer], caching results by comment id.
+///
+/// A cached result is reused as long as the comment content is unchanged.
+/// Once [MAX_CACHED_COMMENTS] are cached, the oldest entries are evicted.
+#[derive(Clone)]
+pub(crate) struct CachedTransformer {
+    transformer: Arc<dyn TextTransformer>,
+    cache: Arc<Mutex<Cache>>,
+}
+
+#[derive(Default)]
+struct Cache {
+    /// Comment id => (original content, transformed content).
+    entries: HashMap<String, (String, String)>,
+    /// Comment ids, oldest first.
+    order: VecDeque<String>,
+}
+
+impl Cache {
+    fn insert(&mut self, id: String, entry: (String, String)) {
+        if self.entries.insert(id.clone(), entry).is_none() {
+            self.order.push_back(id);
+        }
+        while self.order.len() > MAX_CACHED_COMMENTS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl CachedTransformer {
+    pub fn new(transformer: Arc<dyn TextTransformer>) -> Self {
+        Self {
+            transformer,
+            cache: Arc::new(Mutex::new(Cache::default())),
+        }
+    }
+
+    pub fn apply(&self, comment: &mut Comment) {
+        let cached = self
+            .cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entries
+            .get(&comment.id)
+            .filter(|(original, _)| *original == comment.content_html)
+            .map(|(_, transformed)| transformed.clone());
+
+        let transformed = match cached {
+            Some(transformed) => transformed,
+            None => {
+                // Not holding the lock here, transformers may be slow.
+                let transformed =
+                    self.transformer.transform(&comment.content_html);
+                self.cache
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .insert(
+                        comment.id.clone(),
+                        (comment.content_html.clone(), transformed.clone()),
+                    );
+                transformed
+            }
+        };
+        comment.content_html = transformed;
+    }
+}
+
+impl std::fmt::Debug for CachedTransformer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("CachedTransformer")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let mut cache = Cache::default();
+        for id in 0..=MAX_CACHED_COMMENTS {
+            cache.insert(id.to_string(), (String::new(), String::new()));
+        }
+        // Updating an entry does not count twice.
+        cache.insert("1".into(), ("a".into(), "b".into()));
+        assert_eq!(cache.entries.len(), MAX_CACHED_COMMENTS);
+        assert!(!cache.entries.contains_key("0"));
+        assert_eq!(cache.entries["1"].1, "b");
+    }
+}
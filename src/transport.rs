@@ -0,0 +1,501 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use super::{error::Error, redirect::RedirectPolicy};
+
+/// HTTP method of a [Request].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// A request made by the client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Request {
+    pub method: Method,
+    /// Absolute URL.
+    pub url: String,
+    /// Extra headers, in addition to the client defaults.
+    pub headers: Vec<(String, String)>,
+    /// Form fields, sent url-encoded with POST requests.
+    pub form: Vec<(String, String)>,
+    /// Id shared by all requests of one logical operation, eg. the form
+    /// fetch and the post when creating a poll.
+    ///
+    /// Set by the client before the request hooks run.
+    pub correlation_id: Option<String>,
+    /// Give up after this long, see [crate::RequestOptions::timeout].
+    pub timeout: Option<Duration>,
+}
+
+/// A new random correlation id, see [Request::correlation_id].
+pub(crate) fn new_correlation_id() -> String {
+    format!("{:016x}", super::retry::random())
+}
+
+impl Request {
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: Method::Get,
+            url: url.into(),
+            headers: Vec::new(),
+            form: Vec::new(),
+            correlation_id: None,
+            timeout: None,
+        }
+    }
+
+    pub fn post<K, V>(
+        url: impl Into<String>,
+        form: impl IntoIterator<Item = (K, V)>,
+    ) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Self {
+            method: Method::Post,
+            url: url.into(),
+            headers: Vec::new(),
+            form: form
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+            correlation_id: None,
+            timeout: None,
+        }
+    }
+}
+
+/// A response received by the client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    /// Final URL, after following redirects.
+    pub url: String,
+    /// URLs that redirected on the way to `url`, in order.
+    pub redirects: Vec<String>,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    /// Target of a redirect that was not followed, as sent by the server.
+    pub fn location(&self) -> Option<&str> {
+        match self.status {
+            300..=399 => self.header("location"),
+            _ => None,
+        }
+    }
+
+    /// Value of the first header named `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Sends the requests of a [crate::Client].
+///
+/// The default transport uses reqwest. Implement this to route requests
+/// through another HTTP stack, or use [MockTransport] in tests.
+///
+/// Transports follow redirects themselves, recording them in
+/// [Response::redirects], and return error statuses as regular responses;
+/// the client maps them to errors. Custom transports decide on their own
+/// which redirects to follow.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: &Request) -> Result<Response, Error>;
+
+    /// Whether the transport gives up after [Request::timeout] itself.
+    ///
+    /// Otherwise the client sends requests with a timeout on a helper
+    /// thread, and stops waiting for it once the timeout passed.
+    fn enforces_timeout(&self) -> bool {
+        false
+    }
+}
+
+/// A shared transport, debug-printable for [crate::ClientBuilder].
+#[derive(Clone)]
+pub(crate) struct Shared(pub std::sync::Arc<dyn Transport>);
+
+impl std::fmt::Debug for Shared {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("Transport")
+    }
+}
+
+/// Builds a reqwest client with the given timeout.
+pub(crate) type BuildClient =
+    dyn Fn(Option<Duration>) -> Result<reqwest::Client, Error> + Send + Sync;
+
+/// Clients kept for requests with their own timeout.
+const MAX_TIMED_CLIENTS: usize = 8;
+
+/// The default transport.
+pub(crate) struct ReqwestTransport {
+    client: reqwest::Client,
+    /// Builds clients for requests with their own timeout, as reqwest only
+    /// supports timeouts per client. Unset for clients supplied by the
+    /// user, which can not be rebuilt.
+    build: Option<Box<BuildClient>>,
+    /// Clients by timeout, most recently built last.
+    timed: Mutex<Vec<(Duration, reqwest::Client)>>,
+    /// Cookies shared by all clients, so they use the same session.
+    cookies: Option<Mutex<cookie_store::CookieStore>>,
+    max_bytes: Option<usize>,
+    redirects: RedirectPolicy,
+}
+
+impl ReqwestTransport {
+    /// Use a client supplied by the user, with its own cookie handling.
+    pub fn new(
+        client: reqwest::Client,
+        max_bytes: Option<usize>,
+        redirects: RedirectPolicy,
+    ) -> Self {
+        Self {
+            client,
+            build: None,
+            timed: Mutex::new(Vec::new()),
+            cookies: None,
+            max_bytes,
+            redirects,
+        }
+    }
+
+    /// Use clients from `build`, which must not keep cookies themselves.
+    pub fn with_builder(
+        build: Box<BuildClient>,
+        cookies: bool,
+        max_bytes: Option<usize>,
+        redirects: RedirectPolicy,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            client: build(None)?,
+            build: Some(build),
+            timed: Mutex::new(Vec::new()),
+            cookies: if cookies {
+                Some(Mutex::new(cookie_store::CookieStore::default()))
+            } else {
+                None
+            },
+            max_bytes,
+            redirects,
+        })
+    }
+
+    /// The client to send a request with the given timeout.
+    fn client(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<reqwest::Client, Error> {
+        let (timeout, build) = match (timeout, &self.build) {
+            (Some(timeout), Some(build)) => (timeout, build),
+            _ => return Ok(self.client.clone()),
+        };
+        let mut timed = self
+            .timed
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((_, client)) = timed.iter().find(|(t, _)| *t == timeout) {
+            return Ok(client.clone());
+        }
+        let client = build(Some(timeout))?;
+        if timed.len() >= MAX_TIMED_CLIENTS {
+            timed.remove(0);
+        }
+        timed.push((timeout, client.clone()));
+        Ok(client)
+    }
+
+    fn send_once(&self, request: &Request) -> Result<reqwest::Response, Error> {
+        let client = self.client(request.timeout)?;
+        let mut builder = match request.method {
+            Method::Get => client.get(&request.url),
+            Method::Post => client.post(&request.url).form(&request.form),
+        };
+        for (key, value) in &request.headers {
+            builder = builder.header(key.as_str(), value.as_str());
+        }
+        let url = reqwest::Url::parse(&request.url).ok();
+        if let (Some(cookies), Some(url)) = (&self.cookies, &url) {
+            let cookies = cookies
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .get_request_cookies(url)
+                .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+                .collect::<Vec<_>>()
+                .join("; ");
+            if !cookies.is_empty() {
+                builder = builder.header(reqwest::header::COOKIE, cookies);
+            }
+        }
+
+        let res = builder.send().map_err(|e| match request.timeout {
+            Some(after) if e.is_timeout() => Error::Timeout { after },
+            _ => e.into(),
+        })?;
+        if let Some(cookies) = &self.cookies {
+            let received = res
+                .headers()
+                .get_all(reqwest::header::SET_COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .filter_map(|value| {
+                    cookie::Cookie::parse(value.to_string()).ok()
+                })
+                .collect::<Vec<_>>();
+            cookies
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .store_response_cookies(received.into_iter(), res.url());
+        }
+        Ok(res)
+    }
+}
+
+/// Target of a redirect response, resolved against the request URL.
+fn redirect_target(res: &reqwest::Response) -> Option<String> {
+    if !res.status().is_redirection() {
+        return None;
+    }
+    let location = res.headers().get(reqwest::header::LOCATION)?;
+    let target = res.url().join(location.to_str().ok()?).ok()?;
+    Some(target.to_string())
+}
+
+/// Whether two URLs share scheme, host and port.
+fn same_origin(a: &str, b: &str) -> bool {
+    match (reqwest::Url::parse(a), reqwest::Url::parse(b)) {
+        (Ok(a), Ok(b)) => a.origin() == b.origin(),
+        _ => false,
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        use std::io::Read;
+
+        let mut request = request.clone();
+        let mut redirects = Vec::new();
+        let mut res = self.send_once(&request)?;
+        while let Some(target) = redirect_target(&res) {
+            if !self
+                .redirects
+                .follow(&request.url, &target, redirects.len())?
+            {
+                break;
+            }
+            // Like browsers, only 307 and 308 repeat a POST.
+            let status = res.status().as_u16();
+            if status != 307 && status != 308 {
+                request.method = Method::Get;
+                request.form.clear();
+            }
+            // Extra headers may hold credentials meant for HN only.
+            if !same_origin(&request.url, &target) {
+                request.headers.clear();
+            }
+            redirects.push(std::mem::replace(&mut request.url, target));
+            res = self.send_once(&request)?;
+        }
+
+        let status = res.status().as_u16();
+        let url = res.url().to_string();
+        let headers = res
+            .headers()
+            .iter()
+            .filter_map(|(key, value)| {
+                Some((key.as_str().to_string(), value.to_str().ok()?.into()))
+            })
+            .collect();
+        let body = match self.max_bytes {
+            Some(max) => {
+                let too_large = Error::TooLarge {
+                    limit: max,
+                    unit: "bytes",
+                };
+                if res.content_length().is_some_and(|len| len > max as u64) {
+                    return Err(too_large);
+                }
+                let mut body = Vec::new();
+                res.by_ref().take(max as u64 + 1).read_to_end(&mut body)?;
+                if body.len() > max {
+                    return Err(too_large);
+                }
+                String::from_utf8_lossy(&body).into_owned()
+            }
+            None => res.text()?,
+        };
+
+        Ok(Response {
+            status,
+            url,
+            redirects,
+            headers,
+            body,
+        })
+    }
+
+    fn enforces_timeout(&self) -> bool {
+        self.build.is_some()
+    }
+}
+
+/// A transport serving canned responses, for testing without network
+/// access.
+///
+/// Routes are keyed by method and path relative to the site root, eg.
+/// `news?p=1`, so they work with any base URL. Unknown routes get an empty
+/// 404 response. Registering a route again queues another response: they
+/// are served in order, and the last one repeats.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use hackernews_api::{transport::MockTransport, Client};
+/// let mock = Arc::new(MockTransport::new().page("user?id=pg", "<html/>"));
+/// let client = Client::builder().transport(mock.clone()).build().unwrap();
+/// let _ = client.user("pg");
+/// assert_eq!(mock.requests().len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    routes: HashMap<(Method, String), Vec<MockRoute>>,
+    /// Requests served per route.
+    served: Mutex<HashMap<(Method, String), usize>>,
+    requests: Mutex<Vec<Request>>,
+}
+
+#[derive(Debug)]
+struct MockRoute {
+    status: u16,
+    /// Path the response appears to come from, for redirects.
+    location: Option<String>,
+    body: String,
+}
+
+/// Path and query of a URL, without the leading slash.
+fn relative_path(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    rest.find('/').map_or("", |index| &rest[index + 1..])
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `html` for GET requests to `path`.
+    pub fn page(self, path: &str, html: &str) -> Self {
+        self.respond(Method::Get, path, 200, html)
+    }
+
+    /// Serve a response with the given status and body.
+    pub fn respond(
+        mut self,
+        method: Method,
+        path: &str,
+        status: u16,
+        body: &str,
+    ) -> Self {
+        self.routes
+            .entry((method, path.to_string()))
+            .or_default()
+            .push(MockRoute {
+                status,
+                location: None,
+                body: body.to_string(),
+            });
+        self
+    }
+
+    /// Answer as if the request was redirected to `target`, like HN does
+    /// after logging in or voting.
+    pub fn redirect(
+        mut self,
+        method: Method,
+        path: &str,
+        target: &str,
+        body: &str,
+    ) -> Self {
+        self.routes
+            .entry((method, path.to_string()))
+            .or_default()
+            .push(MockRoute {
+                status: 200,
+                location: Some(target.to_string()),
+                body: body.to_string(),
+            });
+        self
+    }
+
+    /// All requests sent so far, in order.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(request.clone());
+
+        let path = relative_path(&request.url);
+        let root = &request.url[..request.url.len() - path.len()];
+        let key = (request.method, path.to_string());
+        let route = self.routes.get(&key).map(|routes| {
+            let mut served = self
+                .served
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let count = served.entry(key).or_insert(0);
+            *count += 1;
+            &routes[(*count - 1).min(routes.len() - 1)]
+        });
+        let response = match route {
+            Some(route) => Response {
+                status: route.status,
+                url: match &route.location {
+                    Some(target) => format!("{}{}", root, target),
+                    None => request.url.clone(),
+                },
+                redirects: match route.location {
+                    Some(_) => vec![request.url.clone()],
+                    None => Vec::new(),
+                },
+                headers: Vec::new(),
+                body: route.body.clone(),
+            },
+            None => Response {
+                status: 404,
+                url: request.url.clone(),
+                redirects: Vec::new(),
+                headers: Vec::new(),
+                body: String::new(),
+            },
+        };
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_origin() {
+        assert!(same_origin("https://hn.test/login", "https://hn.test/news"));
+        assert!(!same_origin("https://hn.test/", "http://hn.test/"));
+        assert!(!same_origin("https://hn.test/", "https://hn.test:8080/"));
+        assert!(!same_origin("https://hn.test/", "https://example.com/"));
+    }
+}
@@ -1,4 +1,11 @@
+use super::{
+    enrich::{Enricher, Enrichment},
+    media::{self, MediaKind},
+    parse,
+};
+
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum VoteAction {
     Upvote(String),
     Downvote(String),
@@ -7,41 +14,649 @@ pub enum VoteAction {
 impl VoteAction {
     pub(crate) fn url(&self) -> &str {
         match self {
-            Self::Upvote(ref url) => &url,
-            Self::Downvote(ref url) => &url,
+            Self::Upvote(ref url) => url,
+            Self::Downvote(ref url) => url,
         }
     }
 
     pub fn is_upvote(&self) -> bool {
-        match self {
-            Self::Upvote(_) => true,
-            _ => false,
+        matches!(self, Self::Upvote(_))
+    }
+}
+
+/// URL of an item's page on the site at `base_url`, which ends in a slash.
+pub(crate) fn item_url(base_url: &str, id: &str) -> String {
+    format!("{}item?id={}", base_url, id)
+}
+
+/// Title prefixes HN uses for its special post categories.
+const TITLE_PREFIXES: &[&str] =
+    &["Show HN:", "Ask HN:", "Tell HN:", "Launch HN:"];
+
+/// Stable 64-bit FNV-1a hash over whitespace-normalized fields.
+///
+/// Unlike `std::hash`, the result is guaranteed not to change between
+/// releases or platforms, so it can be persisted.
+fn content_hash(fields: &[&str]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut hash = OFFSET;
+    let mut write = |byte: u8| {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(PRIME);
+    };
+    for field in fields {
+        for (index, word) in field.split_whitespace().enumerate() {
+            if index > 0 {
+                write(b' ');
+            }
+            word.bytes().for_each(&mut write);
         }
+        // Never occurs in UTF-8, so fields can not run into each other.
+        write(0xff);
     }
+    hash
+}
+
+/// Which vote actions HN offered on an item.
+///
+/// Unlike the [VoteAction] links this also records arrows that were
+/// rendered but hidden.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VoteAvailability {
+    /// A visible upvote arrow is shown.
+    pub can_up: bool,
+    /// A visible downvote arrow is shown.
+    pub can_down: bool,
+    /// The current user has voted and may unvote.
+    pub already_voted: bool,
+}
+
+/// An item fetched with [crate::Client::item].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+// Boxing the post would only save space for the rare stubs.
+#[allow(clippy::large_enum_variant)]
+pub enum Item {
+    Post(Post),
+    /// HN served a stub instead of the item.
+    Unavailable {
+        reason: UnavailableReason,
+    },
+}
+
+/// Why an [Item] is unavailable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum UnavailableReason {
+    Deleted,
+    /// Killed by moderators or software; hidden unless `showdead` is on.
+    Dead,
+    /// Flagged by users; hidden unless `showdead` is on.
+    Flagged,
+}
+
+/// What kind of submission a [Post] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PostKind {
+    /// A regular story, including Ask HN and Show HN posts.
+    Story,
+    /// A job posting by a YC company. Jobs have no author, score or
+    /// comments.
+    Job,
 }
 
+/// A submission.
+///
+/// Construct one with [Post::new] and set the remaining fields; new fields
+/// may be added in future releases.
+#[non_exhaustive]
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Post {
     pub id: String,
     pub title: String,
-    pub url: String,
-    pub username: String,
-    pub score: u64,
+    /// Link target; `None` for self posts like Ask HN.
+    pub url: Option<String>,
+    pub kind: PostKind,
+    /// Submitter; `None` for job postings.
+    pub username: Option<String>,
+    /// `None` for job postings.
+    pub score: Option<u64>,
     pub comment_count: u64,
-    pub comments: Vec<Comment>,
+    /// Comments in display order; the tree is given by [Comment::depth].
+    pub comments: Ordered<Comment>,
 
     pub vote: Option<VoteAction>,
+    pub votes: VoteAvailability,
+
+    /// Details of the subtext line below the title.
+    pub meta: PostMeta,
+    /// Links offered in the subtext line.
+    pub actions: PostActions,
+
+    /// Metadata attached by [Post::enrich].
+    pub enrichments: Vec<Enrichment>,
+
+    /// Set if the thread continues on further pages, ie. `comments` is
+    /// incomplete.
+    pub truncated: Option<MoreComments>,
+
+    /// The page could not be parsed fully; only the id and title, taken
+    /// from the page title, are set.
+    pub partial: bool,
+}
+
+/// Details of a post's subtext line, beyond the fields on [Post].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PostMeta {
+    /// Element id of the score, eg. `score_123`.
+    pub score_id: Option<String>,
+    /// Link to the submitter's profile.
+    pub user_url: Option<String>,
+    /// Relative age, eg. `3 hours ago`.
+    pub age: Option<String>,
+    /// Submission time as a Unix timestamp, in seconds.
+    pub timestamp: Option<u64>,
+    /// Link behind the age, usually the discussion page.
+    pub age_url: Option<String>,
+    /// Site shown next to the title, eg. `github.com`.
+    pub site: Option<String>,
+}
+
+/// Links in a post's subtext line, relative to the site root.
+///
+/// Which links are shown depends on the page and whether the client is
+/// logged in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PostActions {
+    pub hide: Option<String>,
+    /// Search for earlier submissions of the same URL.
+    pub past: Option<String>,
+    pub flag: Option<String>,
+    pub favorite: Option<String>,
+    /// Web search for the title.
+    pub web: Option<String>,
+}
+
+/// Placeholder for comments on further pages of a thread.
+///
+/// Fetch them with [crate::Client::more_comments], or get the whole
+/// thread at once with [crate::Client::submission_all_pages].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MoreComments {
+    /// Next page of the thread, relative to the site root.
+    pub url: String,
+    /// Number of comments not included, according to the reported comment
+    /// count.
+    pub remaining: u64,
+}
+
+impl Post {
+    /// A post with the given id and title, and all other fields empty.
+    pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            url: None,
+            kind: PostKind::Story,
+            username: None,
+            score: None,
+            comment_count: 0,
+            comments: Ordered::default(),
+            vote: None,
+            votes: VoteAvailability::default(),
+            meta: PostMeta::default(),
+            actions: PostActions::default(),
+            enrichments: Vec::new(),
+            truncated: None,
+            partial: false,
+        }
+    }
+
+    /// Stable hash of the submission content (id, title, url, author).
+    ///
+    /// Volatile data like score, comment count and comments is excluded.
+    pub fn content_hash(&self) -> u64 {
+        let url = self.url.as_deref().unwrap_or("");
+        let username = self.username.as_deref().unwrap_or("");
+        content_hash(&[&self.id, &self.title, url, username])
+    }
+
+    /// The title without a leading `Show HN:`, `Ask HN:`, `Tell HN:` or
+    /// `Launch HN:`.
+    pub fn title_without_prefix(&self) -> &str {
+        TITLE_PREFIXES
+            .iter()
+            .find_map(|prefix| {
+                let head = self.title.get(..prefix.len())?;
+                if head.eq_ignore_ascii_case(prefix) {
+                    Some(self.title[prefix.len()..].trim_start())
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(&self.title)
+    }
+
+    /// Canonical URL of the discussion page on news.ycombinator.com.
+    ///
+    /// See [crate::Client::discussion_url] for the client's base URL.
+    pub fn discussion_url(&self) -> String {
+        item_url(super::DEFAULT_BASE_URL, &self.id)
+    }
+
+    /// URL of the discussion page, scrolled to a comment.
+    pub fn comment_url(&self, comment: &Comment) -> String {
+        format!("{}#{}", self.discussion_url(), comment.id)
+    }
+
+    /// Run the story URL through the given enrichers, collecting their
+    /// results in [Post::enrichments].
+    pub fn enrich(&mut self, enrichers: &[&dyn Enricher]) {
+        if let Some(url) = &self.url {
+            self.enrichments
+                .extend(enrichers.iter().filter_map(|e| e.enrich(url)));
+        }
+    }
+
+    /// Classify the linked story.
+    ///
+    /// The `[pdf]` and `[video]` title markers take precedence over the URL.
+    pub fn media_kind(&self) -> MediaKind {
+        media::classify_title(&self.title)
+            .or_else(|| self.url.as_deref().map(media::classify_url))
+            .unwrap_or(MediaKind::Other)
+    }
 }
 
+/// A comment.
+///
+/// Construct one with [Comment::new] and set the remaining fields; new
+/// fields may be added in future releases.
+#[non_exhaustive]
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Comment {
     pub id: String,
     pub depth: u32,
     pub age: String,
+    /// Posting time as a Unix timestamp, in seconds.
+    pub timestamp: Option<u64>,
     pub username: String,
     pub content_html: String,
     pub children: Vec<Comment>,
 
     pub upvote: Option<VoteAction>,
     pub downvote: Option<VoteAction>,
+    pub votes: VoteAvailability,
+
+    /// Profile of the author, set by
+    /// [crate::Client::submission_with_profiles].
+    pub author_profile: Option<User>,
+}
+
+impl Comment {
+    /// A top-level comment with the given author and text, and all other
+    /// fields empty.
+    pub fn new(
+        id: impl Into<String>,
+        username: impl Into<String>,
+        content_html: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            depth: 0,
+            age: String::new(),
+            timestamp: None,
+            username: username.into(),
+            content_html: content_html.into(),
+            children: Vec::new(),
+            upvote: None,
+            downvote: None,
+            votes: VoteAvailability::default(),
+            author_profile: None,
+        }
+    }
+
+    /// Stable hash of the comment content (id, author, text).
+    ///
+    /// The relative age and vote state are excluded, so the hash only
+    /// changes when the comment is edited.
+    pub fn content_hash(&self) -> u64 {
+        content_hash(&[&self.id, &self.username, &self.content_html])
+    }
+
+    /// Build the reply tree from comments in thread order.
+    ///
+    /// Returns the top-level comments, with replies moved into
+    /// [Comment::children].
+    pub(crate) fn nest(comments: Vec<Comment>) -> Vec<Comment> {
+        fn attach(
+            roots: &mut Vec<Comment>,
+            stack: &mut [Comment],
+            comment: Comment,
+        ) {
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(comment),
+                None => roots.push(comment),
+            }
+        }
+
+        let mut roots = Vec::new();
+        let mut stack: Vec<Comment> = Vec::new();
+        for comment in comments {
+            while stack.last().is_some_and(|top| top.depth >= comment.depth) {
+                let done = stack.pop().unwrap();
+                attach(&mut roots, &mut stack, done);
+            }
+            stack.push(comment);
+        }
+        while let Some(done) = stack.pop() {
+            attach(&mut roots, &mut stack, done);
+        }
+        roots
+    }
+
+    /// Canonical URL of the comment's own page on news.ycombinator.com.
+    ///
+    /// See [crate::Client::permalink] for the client's base URL.
+    pub fn permalink(&self) -> String {
+        item_url(super::DEFAULT_BASE_URL, &self.id)
+    }
+
+    /// All links in the comment text, with their classification.
+    pub fn media_links(&self) -> Vec<(String, MediaKind)> {
+        parse::parse_links(&self.content_html)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|url| {
+                let kind = media::classify_url(&url);
+                (url, kind)
+            })
+            .collect()
+    }
+
+    /// The comment HTML restricted to the markup HN itself produces.
+    ///
+    /// Only `p`, `a`, `i`, `pre` and `code` are kept, links are limited to
+    /// http(s) and marked `nofollow`, and the reply link is removed.
+    #[cfg(feature = "sanitize")]
+    pub fn content_html_sanitized(&self) -> String {
+        ammonia::Builder::empty()
+            .add_tags(&["p", "a", "i", "pre", "code"])
+            .add_tag_attributes("a", &["href"])
+            .clean_content_tags(["div"].iter().cloned().collect())
+            .url_schemes(["http", "https"].iter().cloned().collect())
+            .link_rel(Some("nofollow noopener noreferrer"))
+            .clean(&self.content_html)
+            .to_string()
+    }
+}
+
+/// Continuation of a paginated listing, parsed from the "More" link.
+///
+/// HN uses two pagination styles: `?p=<page>` for most listings and
+/// `?next=<id>&n=<offset>` for `newest` and friends.
+/// Both are followed the same way with [crate::Client::follow].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Cursor {
+    Page {
+        href: String,
+        page: u64,
+    },
+    Next {
+        href: String,
+        next: String,
+        offset: u64,
+    },
+}
+
+impl Cursor {
+    /// Link target, relative to the site root.
+    pub fn href(&self) -> &str {
+        match self {
+            Self::Page { ref href, .. } => href,
+            Self::Next { ref href, .. } => href,
+        }
+    }
+}
+
+/// Items in the order HN displays them.
+///
+/// Listings are in rank order. Comments are in thread order, which is a
+/// pre-order traversal of the comment tree: each comment is followed by
+/// its replies.
+///
+/// Dereferences to a slice; items can be modified but not reordered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Ordered<T>(Vec<T>);
+
+impl<T> Ordered<T> {
+    pub(crate) fn new(items: Vec<T>) -> Self {
+        Self(items)
+    }
+
+    /// Append items that HN displays after the current ones, eg. from the
+    /// next page.
+    pub(crate) fn extend(&mut self, items: Ordered<T>) {
+        self.0.extend(items.0);
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.0.iter_mut()
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> Default for Ordered<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T> std::ops::Deref for Ordered<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T> IntoIterator for Ordered<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Ordered<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// A single page of a listing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Page<T> {
+    /// Items in rank order.
+    pub items: Ordered<T>,
+    /// Cursor for the following page, if there is one.
+    pub next: Option<Cursor>,
+    /// `true` if there are no further pages.
+    ///
+    /// Pages without rows or without a "More" link are the last page.
+    pub is_last: bool,
+}
+
+/// A fetched value along with its provenance and response metadata.
+///
+/// Dereferences to the wrapped value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Fetched<T> {
+    pub value: T,
+    pub fetched_at: std::time::SystemTime,
+    /// Final URL the value was read from, after redirects.
+    pub source_url: String,
+    /// HTTP status code of the response.
+    pub status: u16,
+    /// URLs that redirected on the way to `source_url`, in order.
+    pub redirects: Vec<String>,
+    /// Target of a redirect that was not followed, see
+    /// [crate::RedirectPolicy].
+    pub location: Option<String>,
+    /// Time from sending the request to reading the full response.
+    ///
+    /// Only covers the final attempt if the request was retried.
+    pub latency: std::time::Duration,
+    /// Size of the response body in bytes.
+    pub size: usize,
+}
+
+impl<T> Fetched<T> {
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Transform the value, keeping the fetch metadata.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Fetched<U> {
+        Fetched {
+            value: f(self.value),
+            fetched_at: self.fetched_at,
+            source_url: self.source_url,
+            status: self.status,
+            redirects: self.redirects,
+            location: self.location,
+            latency: self.latency,
+            size: self.size,
+        }
+    }
+
+    /// Fallibly transform the value, keeping the fetch metadata.
+    pub fn try_map<U, E>(
+        self,
+        f: impl FnOnce(T) -> Result<U, E>,
+    ) -> Result<Fetched<U>, E> {
+        Ok(Fetched {
+            value: f(self.value)?,
+            fetched_at: self.fetched_at,
+            source_url: self.source_url,
+            status: self.status,
+            redirects: self.redirects,
+            location: self.location,
+            latency: self.latency,
+            size: self.size,
+        })
+    }
+}
+
+impl<T> std::ops::Deref for Fetched<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+/// A user profile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct User {
+    pub username: String,
+    /// Account creation date as displayed, eg. `October 9, 2006`.
+    pub created: String,
+    pub karma: u64,
+    pub about_html: Option<String>,
+}
+
+/// Actions available to a logged in account.
+///
+/// See [crate::AuthenticatedClient::capabilities].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Capabilities {
+    pub karma: u64,
+    pub can_downvote: bool,
+    pub can_flag: bool,
+    pub can_create_polls: bool,
+    /// The `showdead` profile setting is enabled.
+    pub sees_dead: bool,
+}
+
+/// Result of a health check, see [crate::Client::health_check].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Health {
+    /// HN answered normally.
+    Ok,
+    /// HN is throttling this client.
+    RateLimited {
+        retry_hint: Option<std::time::Duration>,
+    },
+    /// HN refuses to serve this client, usually because of an IP ban.
+    Blocked,
+    /// HN is down or could not be reached.
+    Down,
+    /// The session of an [crate::AuthenticatedClient] has expired.
+    LoggedOut,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::thread_fixture;
+
+    #[test]
+    fn test_nest_comments() {
+        let html = thread_fixture(&[(2, 0), (3, 1), (4, 2), (5, 1), (6, 0)]);
+        let post = parse::parse_submission(
+            "1".into(),
+            scraper::Html::parse_document(&html),
+        )
+        .unwrap();
+
+        let tree = Comment::nest(post.comments.into_vec());
+        let ids = |comments: &[Comment]| {
+            comments.iter().map(|c| c.id.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&tree), vec!["2", "6"]);
+        assert_eq!(ids(&tree[0].children), vec!["3", "5"]);
+        assert_eq!(ids(&tree[0].children[0].children), vec!["4"]);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let html = thread_fixture(&[(2, 0), (3, 1)]);
+        let post = parse::parse_submission(
+            "1".into(),
+            scraper::Html::parse_document(&html),
+        )
+        .unwrap();
+
+        let mut edited = post.comments[1].clone();
+        assert_eq!(edited.content_hash(), post.comments[1].content_hash());
+        edited.content_html = edited.content_html.replace("  ", "\n ");
+        assert_eq!(edited.content_hash(), post.comments[1].content_hash());
+        edited.content_html.push_str("edit");
+        assert_ne!(edited.content_hash(), post.comments[1].content_hash());
+        assert_ne!(post.comments[0].content_hash(), edited.content_hash());
+    }
 }
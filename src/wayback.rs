@@ -0,0 +1,67 @@
+//! Recover threads from Wayback Machine snapshots.
+
+use super::{
+    error::Error,
+    parse::{self, Layout},
+    transport::Request,
+    types::{Fetched, Post},
+    Client,
+};
+
+const AVAILABILITY_URL: &str = "https://archive.org/wayback/available";
+
+/// Get a thread as it looked at a point in time.
+///
+/// `timestamp` is in Wayback format, `YYYYMMDDhhmmss`; a prefix like
+/// `20120315` is fine. The snapshot closest to it is fetched from
+/// web.archive.org and parsed in the matching [Layout]. Fails with
+/// [Error::NotFound] if the item was never archived.
+pub fn submission_at(
+    client: &Client,
+    id: &str,
+    timestamp: &str,
+) -> Result<Fetched<Post>, Error> {
+    let target = format!("news.ycombinator.com/item?id={}", id);
+    let lookup = format!(
+        "{}?url={}&timestamp={}",
+        AVAILABILITY_URL,
+        target.replace('?', "%3F").replace('=', "%3D"),
+        timestamp
+    );
+    let res = client.send(Request::get(lookup), None)?;
+    let availability: serde_json::Value = serde_json::from_str(&res.body)
+        .map_err(|e| parse::ParseError::new(e.to_string()))?;
+    let closest = &availability["archived_snapshots"]["closest"];
+    let snapshot_time = closest["timestamp"]
+        .as_str()
+        .filter(|_| closest["available"].as_bool().unwrap_or(false))
+        .ok_or_else(|| Error::NotFound(format!("No snapshot of {}", target)))?;
+
+    // The `id_` flag returns the original page without the archive toolbar.
+    let url = format!(
+        "https://web.archive.org/web/{}id_/https://{}",
+        snapshot_time, target
+    );
+    let started = std::time::Instant::now();
+    let res = client.send(Request::get(url), None)?;
+    let latency = started.elapsed();
+    let layout = if res.body.contains("fatitem") {
+        Layout::Current
+    } else {
+        Layout::Archived
+    };
+    let post = parse::parse_submission_html(id, &res.body, layout)?;
+
+    let location = res.location().map(str::to_string);
+
+    Ok(Fetched {
+        value: post,
+        fetched_at: std::time::SystemTime::now(),
+        source_url: res.url,
+        status: res.status,
+        location,
+        redirects: res.redirects,
+        latency,
+        size: res.body.len(),
+    })
+}